@@ -1,9 +1,10 @@
 use crate::database::{
-    ConnectionConfig, ConnectionInfo, DbConnection, DbError, CONNECTIONS,
-    TableInfo, ColumnInfo, QueryResult, TableDataResult, resolve_host
+    self, ConnectionConfig, ConnectionInfo, DbConnection, DbError, CONNECTIONS,
+    TableInfo, ColumnInfo, QueryResult, TableDataResult, KeysetPageResult, resolve_host
 };
 use crate::config;
-use crate::ssh::SshTunnel;
+use crate::ssh::{SshAuth, SshTunnel};
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::sync::Arc;
@@ -13,6 +14,8 @@ use tauri::{AppHandle, Manager, WebviewWindow};
 pub struct CommandResult {
     success: bool,
     message: String,
+    #[serde(rename = "errorDetail")]
+    error_detail: Option<database::StructuredError>,
 }
 
 // ============ 窗口控制 ============
@@ -36,6 +39,17 @@ pub async fn window_close(window: WebviewWindow) {
     let _ = window.close();
 }
 
+// 从连接配置中取出 SSH 认证材料，按私钥 > ssh-agent > 密码的优先级供隧道使用
+fn ssh_auth_from_config(config: &ConnectionConfig) -> SshAuth {
+    SshAuth {
+        password: config.ssh_password.clone(),
+        key_path: config.ssh_key.clone(),
+        key_data: config.ssh_key_data.clone(),
+        key_passphrase: config.ssh_key_passphrase.clone(),
+        use_agent: config.ssh_use_agent.unwrap_or(false),
+    }
+}
+
 // ============ 数据库操作 ============
 
 #[tauri::command]
@@ -51,8 +65,7 @@ pub async fn db_test(config: ConnectionConfig) -> CommandResult {
                 ssh_host,
                 config.ssh_port.unwrap_or(22),
                 config.ssh_user.as_deref().unwrap_or(""),
-                config.ssh_password.as_deref(),
-                config.ssh_key.as_deref(),
+                ssh_auth_from_config(&config),
                 &config.host,
                 config.port,
             ).await {
@@ -65,6 +78,7 @@ pub async fn db_test(config: ConnectionConfig) -> CommandResult {
                     return CommandResult {
                         success: false,
                         message: format!("SSH 隧道失败: {}", e),
+                        error_detail: None,
                     };
                 }
             }
@@ -79,6 +93,7 @@ pub async fn db_test(config: ConnectionConfig) -> CommandResult {
                 &config.username,
                 &config.password,
                 config.database.as_deref(),
+                config.ssl.as_ref(),
             ).await
         }
         "postgres" => {
@@ -88,11 +103,12 @@ pub async fn db_test(config: ConnectionConfig) -> CommandResult {
                 &config.username,
                 &config.password,
                 config.database.as_deref(),
+                config.ssl.as_ref(),
             ).await
         }
         "sqlite" => {
             let path = config.database.as_deref().unwrap_or(&config.host);
-            DbConnection::test_sqlite(path).await
+            DbConnection::test_sqlite(path, config.sqlite.as_ref()).await
         }
         "sqlserver" => {
             DbConnection::test_sqlserver(
@@ -101,6 +117,7 @@ pub async fn db_test(config: ConnectionConfig) -> CommandResult {
                 &config.username,
                 &config.password,
                 config.database.as_deref(),
+                config.ssl.as_ref(),
             ).await
         }
         _ => Err(DbError::UnsupportedType(config.db_type.clone())),
@@ -116,17 +133,22 @@ pub async fn db_test(config: ConnectionConfig) -> CommandResult {
             CommandResult {
                 success: true,
                 message: msg.to_string(),
+                error_detail: None,
+            }
+        }
+        Err(e) => {
+            let detail = e.structured_detail();
+            CommandResult {
+                success: false,
+                message: e.to_string(),
+                error_detail: detail,
             }
         }
-        Err(e) => CommandResult {
-            success: false,
-            message: e.to_string(),
-        },
     }
 }
 
-#[tauri::command]
-pub async fn db_connect(config: ConnectionConfig) -> CommandResult {
+// 建立一次实际的数据库连接（含 SSH 隧道），供 db_connect 与断线重连共用
+async fn establish_connection(config: &ConnectionConfig) -> Result<(DbConnection, Option<SshTunnel>), DbError> {
     let mut target_host = resolve_host(&config.host);
     let mut target_port = config.port;
     let mut ssh_tunnel: Option<SshTunnel> = None;
@@ -134,27 +156,18 @@ pub async fn db_connect(config: ConnectionConfig) -> CommandResult {
     // SSH 隧道
     if config.ssh_enabled.unwrap_or(false) {
         if let Some(ssh_host) = &config.ssh_host {
-            match SshTunnel::create(
+            let tunnel = SshTunnel::create(
                 ssh_host,
                 config.ssh_port.unwrap_or(22),
                 config.ssh_user.as_deref().unwrap_or(""),
-                config.ssh_password.as_deref(),
-                config.ssh_key.as_deref(),
+                ssh_auth_from_config(config),
                 &config.host,
                 config.port,
-            ).await {
-                Ok(tunnel) => {
-                    target_host = "127.0.0.1".to_string();
-                    target_port = tunnel.local_port;
-                    ssh_tunnel = Some(tunnel);
-                }
-                Err(e) => {
-                    return CommandResult {
-                        success: false,
-                        message: format!("SSH 隧道失败: {}", e),
-                    };
-                }
-            }
+            ).await.map_err(|e| DbError::SshError(e.to_string()))?;
+
+            target_host = "127.0.0.1".to_string();
+            target_port = tunnel.local_port;
+            ssh_tunnel = Some(tunnel);
         }
     }
 
@@ -166,6 +179,8 @@ pub async fn db_connect(config: ConnectionConfig) -> CommandResult {
                 &config.username,
                 &config.password,
                 config.database.as_deref(),
+                config.ssl.as_ref(),
+                config.max_pool_size,
             ).await
         }
         "postgres" => {
@@ -175,11 +190,13 @@ pub async fn db_connect(config: ConnectionConfig) -> CommandResult {
                 &config.username,
                 &config.password,
                 config.database.as_deref(),
+                config.ssl.as_ref(),
+                config.max_pool_size,
             ).await
         }
         "sqlite" => {
             let path = config.database.as_deref().unwrap_or(&config.host);
-            DbConnection::connect_sqlite(path).await
+            DbConnection::connect_sqlite(path, config.sqlite.as_ref()).await
         }
         "sqlserver" => {
             DbConnection::connect_sqlserver(
@@ -188,23 +205,61 @@ pub async fn db_connect(config: ConnectionConfig) -> CommandResult {
                 &config.username,
                 &config.password,
                 config.database.as_deref(),
+                config.ssl.as_ref(),
+                config.max_pool_size,
             ).await
         }
         _ => Err(DbError::UnsupportedType(config.db_type.clone())),
+    }?;
+
+    Ok((connection, ssh_tunnel))
+}
+
+// 在瞬时网络故障后重建连接（以及 SSH 隧道，获取一个新的本地端口），替换掉 CONNECTIONS 中失效的条目
+async fn reconnect(id: &str) -> Result<(), DbError> {
+    let config = {
+        let connections = CONNECTIONS.read();
+        match connections.get(id) {
+            Some(info) => info.config.clone(),
+            None => return Err(DbError::NotConnected),
+        }
+    };
+
+    let (connection, ssh_tunnel) = establish_connection(&config).await?;
+    let conn_info = ConnectionInfo {
+        connection,
+        config,
+        ssh_tunnel,
     };
 
-    match connection {
-        Ok(conn) => {
+    let mut connections = CONNECTIONS.write();
+    connections.insert(id.to_string(), Arc::new(conn_info));
+    Ok(())
+}
+
+// 瞬时故障重连后的退避重试：固定次数 + 指数退避上限
+const RECONNECT_MAX_ATTEMPTS: u32 = 3;
+
+async fn backoff_delay(attempt: u32) {
+    let millis = 200u64.saturating_mul(1 << attempt.min(4));
+    tokio::time::sleep(std::time::Duration::from_millis(millis)).await;
+}
+
+#[tauri::command]
+pub async fn db_connect(config: ConnectionConfig) -> CommandResult {
+    let ssh_enabled = config.ssh_enabled.unwrap_or(false);
+    match establish_connection(&config).await {
+        Ok((connection, ssh_tunnel)) => {
             let conn_info = ConnectionInfo {
-                connection: conn,
+                connection,
                 config: config.clone(),
                 ssh_tunnel,
             };
-            
+
             let mut connections = CONNECTIONS.write();
             connections.insert(config.id.clone(), Arc::new(conn_info));
-            
-            let msg = if ssh_tunnel.is_some() {
+
+            let msg = if ssh_enabled {
                 "连接成功 (SSH隧道)"
             } else {
                 "连接成功"
@@ -212,12 +267,17 @@ pub async fn db_connect(config: ConnectionConfig) -> CommandResult {
             CommandResult {
                 success: true,
                 message: msg.to_string(),
+                error_detail: None,
+            }
+        }
+        Err(e) => {
+            let detail = e.structured_detail();
+            CommandResult {
+                success: false,
+                message: e.to_string(),
+                error_detail: detail,
             }
         }
-        Err(e) => CommandResult {
-            success: false,
-            message: e.to_string(),
-        },
     }
 }
 
@@ -225,57 +285,109 @@ pub async fn db_connect(config: ConnectionConfig) -> CommandResult {
 pub async fn db_disconnect(id: String) -> CommandResult {
     let mut connections = CONNECTIONS.write();
     if connections.remove(&id).is_some() {
+        drop(connections);
+        crate::subscription::drop_connection(&id);
         CommandResult {
             success: true,
             message: "断开成功".to_string(),
+            error_detail: None,
         }
     } else {
         CommandResult {
             success: false,
             message: "连接不存在".to_string(),
+            error_detail: None,
         }
     }
 }
 
 #[tauri::command]
 pub async fn db_query(id: String, sql: String) -> QueryResult {
+    let mut attempt = 0;
+    let result = loop {
+        let result = db_query_once(&id, &sql).await;
+
+        let transient = result
+            .error
+            .as_deref()
+            .map(database::is_transient_error_text)
+            .unwrap_or(false);
+
+        if !transient || attempt >= RECONNECT_MAX_ATTEMPTS {
+            break result;
+        }
+
+        backoff_delay(attempt).await;
+        attempt += 1;
+        if reconnect(&id).await.is_err() {
+            break result;
+        }
+    };
+
+    // 改动型语句执行成功后，通知订阅模块重新评估涉及到这些表的订阅
+    if result.error.is_none() && !database::is_row_producing(&sql) {
+        let tables = crate::subscription::extract_referenced_tables(&sql);
+        crate::subscription::notify_tables_changed(&id, &tables).await;
+    }
+
+    result
+}
+
+async fn db_query_once(id: &str, sql: &str) -> QueryResult {
     let connections = CONNECTIONS.read();
-    let conn_info = match connections.get(&id) {
+    let conn_info = match connections.get(id) {
         Some(c) => c.clone(),
         None => return QueryResult {
             columns: vec![],
             rows: vec![],
             error: Some("未连接".to_string()),
             affected_rows: None,
+            error_detail: None,
         },
     };
     drop(connections);
 
     match &conn_info.connection {
         DbConnection::MySql(pool) => {
-            query_mysql(pool, &sql).await
+            query_mysql(pool, sql).await
         }
         DbConnection::Postgres(pool) => {
-            query_postgres(pool, &sql).await
+            query_postgres(pool, sql).await
         }
         DbConnection::Sqlite(pool) => {
-            query_sqlite(pool, &sql).await
+            query_sqlite(pool, sql).await
         }
         DbConnection::SqlServer(conn) => {
-            query_sqlserver(conn, &sql).await
+            query_sqlserver(conn, sql).await
+        }
+    }
+}
+
+// 将一段脚本按顶层分号拆成多条语句依次执行，每条都走一次完整的 db_query（含断线重连），
+// 返回每条语句各自的结果；continue_on_error 为 false（默认）时遇到第一个出错的语句就停止
+#[tauri::command]
+pub async fn db_execute_script(id: String, sql: String, continue_on_error: Option<bool>) -> Vec<QueryResult> {
+    let continue_on_error = continue_on_error.unwrap_or(false);
+    let statements = database::split_statements(&sql);
+    let mut results = Vec::with_capacity(statements.len());
+
+    for statement in statements {
+        let result = db_query(id.clone(), statement).await;
+        let has_error = result.error.is_some();
+        results.push(result);
+        if has_error && !continue_on_error {
+            break;
         }
     }
+
+    results
 }
 
 async fn query_mysql(pool: &sqlx::MySqlPool, sql: &str) -> QueryResult {
     use sqlx::Row;
     
     // 判断是否是查询语句
-    let sql_upper = sql.trim().to_uppercase();
-    let is_select = sql_upper.starts_with("SELECT") || 
-                    sql_upper.starts_with("SHOW") || 
-                    sql_upper.starts_with("DESCRIBE") ||
-                    sql_upper.starts_with("EXPLAIN");
+    let is_select = database::is_row_producing(sql);
 
     if is_select {
         match sqlx::query(sql).fetch_all(pool).await {
@@ -286,6 +398,7 @@ async fn query_mysql(pool: &sqlx::MySqlPool, sql: &str) -> QueryResult {
                         rows: vec![],
                         error: None,
                         affected_rows: None,
+                        error_detail: None,
                     };
                 }
 
@@ -295,29 +408,17 @@ async fn query_mysql(pool: &sqlx::MySqlPool, sql: &str) -> QueryResult {
                     .map(|c| c.name().to_string())
                     .collect();
 
+                let type_names: Vec<String> = rows[0]
+                    .columns()
+                    .iter()
+                    .map(|c| c.type_info().name().to_string())
+                    .collect();
+
                 let data: Vec<Vec<Value>> = rows
                     .iter()
                     .map(|row| {
-                        columns
-                            .iter()
-                            .enumerate()
-                            .map(|(i, _)| {
-                                row.try_get_raw(i)
-                                    .ok()
-                                    .and_then(|v| {
-                                        if v.is_null() {
-                                            Some(Value::Null)
-                                        } else {
-                                            row.try_get::<String, _>(i)
-                                                .map(Value::String)
-                                                .or_else(|_| row.try_get::<i64, _>(i).map(|n| json!(n)))
-                                                .or_else(|_| row.try_get::<f64, _>(i).map(|n| json!(n)))
-                                                .or_else(|_| row.try_get::<bool, _>(i).map(|b| json!(b)))
-                                                .ok()
-                                        }
-                                    })
-                                    .unwrap_or(Value::Null)
-                            })
+                        (0..columns.len())
+                            .map(|i| decode_mysql_cell(row, i, &type_names[i]))
                             .collect()
                     })
                     .collect();
@@ -327,6 +428,7 @@ async fn query_mysql(pool: &sqlx::MySqlPool, sql: &str) -> QueryResult {
                     rows: data,
                     error: None,
                     affected_rows: None,
+                    error_detail: None,
                 }
             }
             Err(e) => QueryResult {
@@ -334,6 +436,7 @@ async fn query_mysql(pool: &sqlx::MySqlPool, sql: &str) -> QueryResult {
                 rows: vec![],
                 error: Some(e.to_string()),
                 affected_rows: None,
+                error_detail: Some(database::classify_sqlx_error(&e)),
             },
         }
     } else {
@@ -343,24 +446,83 @@ async fn query_mysql(pool: &sqlx::MySqlPool, sql: &str) -> QueryResult {
                 rows: vec![],
                 error: None,
                 affected_rows: Some(result.rows_affected() as i64),
+                error_detail: None,
             },
             Err(e) => QueryResult {
                 columns: vec![],
                 rows: vec![],
                 error: Some(e.to_string()),
                 affected_rows: None,
+                error_detail: Some(database::classify_sqlx_error(&e)),
             },
         }
     }
 }
 
+// 二进制数据统一编码成 `{"type":"bytes","b64":"..."}`，与 bind_json_value 绑定参数时
+// 使用的约定保持一致，前端按同一套规则收发
+fn bytes_to_json(bytes: Vec<u8>) -> Value {
+    json!({
+        "type": "bytes",
+        "b64": base64::engine::general_purpose::STANDARD.encode(bytes),
+    })
+}
+
+// 按列的声明类型解码 MySQL 单元格：DATETIME/DATE/TIME 转 RFC3339/ISO 字符串，
+// DECIMAL 用 rust_decimal 保精度转字符串，JSON 原样传 Value，BLOB/BINARY 转 base64，
+// 其余类型沿用原有的 String/i64/f64/bool 级联尝试
+fn decode_mysql_cell(row: &sqlx::mysql::MySqlRow, i: usize, type_name: &str) -> Value {
+    use sqlx::Row;
+
+    if row.try_get_raw(i).map(|v| v.is_null()).unwrap_or(true) {
+        return Value::Null;
+    }
+
+    let tn = type_name.to_ascii_uppercase();
+
+    if tn.contains("JSON") {
+        if let Ok(v) = row.try_get::<serde_json::Value, _>(i) {
+            return v;
+        }
+    }
+    if tn.contains("DECIMAL") {
+        if let Ok(d) = row.try_get::<rust_decimal::Decimal, _>(i) {
+            return Value::String(d.to_string());
+        }
+    }
+    if tn.contains("DATETIME") || tn.contains("TIMESTAMP") {
+        if let Ok(dt) = row.try_get::<chrono::NaiveDateTime, _>(i) {
+            return Value::String(dt.and_utc().to_rfc3339());
+        }
+    }
+    if tn == "DATE" {
+        if let Ok(d) = row.try_get::<chrono::NaiveDate, _>(i) {
+            return Value::String(d.to_string());
+        }
+    }
+    if tn == "TIME" {
+        if let Ok(t) = row.try_get::<chrono::NaiveTime, _>(i) {
+            return Value::String(t.to_string());
+        }
+    }
+    if tn.contains("BLOB") || tn.contains("BINARY") {
+        if let Ok(b) = row.try_get::<Vec<u8>, _>(i) {
+            return bytes_to_json(b);
+        }
+    }
+
+    row.try_get::<String, _>(i)
+        .map(Value::String)
+        .or_else(|_| row.try_get::<i64, _>(i).map(|n| json!(n)))
+        .or_else(|_| row.try_get::<f64, _>(i).map(|n| json!(n)))
+        .or_else(|_| row.try_get::<bool, _>(i).map(|b| json!(b)))
+        .unwrap_or(Value::Null)
+}
+
 async fn query_postgres(pool: &sqlx::PgPool, sql: &str) -> QueryResult {
     use sqlx::Row;
     
-    let sql_upper = sql.trim().to_uppercase();
-    let is_select = sql_upper.starts_with("SELECT") || 
-                    sql_upper.starts_with("SHOW") ||
-                    sql_upper.starts_with("EXPLAIN");
+    let is_select = database::is_row_producing(sql);
 
     if is_select {
         match sqlx::query(sql).fetch_all(pool).await {
@@ -371,6 +533,7 @@ async fn query_postgres(pool: &sqlx::PgPool, sql: &str) -> QueryResult {
                         rows: vec![],
                         error: None,
                         affected_rows: None,
+                        error_detail: None,
                     };
                 }
 
@@ -379,30 +542,17 @@ async fn query_postgres(pool: &sqlx::PgPool, sql: &str) -> QueryResult {
                     .iter()
                     .map(|c| c.name().to_string())
                     .collect();
+                let type_names: Vec<String> = rows[0]
+                    .columns()
+                    .iter()
+                    .map(|c| c.type_info().name().to_string())
+                    .collect();
 
                 let data: Vec<Vec<Value>> = rows
                     .iter()
                     .map(|row| {
-                        columns
-                            .iter()
-                            .enumerate()
-                            .map(|(i, _)| {
-                                row.try_get_raw(i)
-                                    .ok()
-                                    .and_then(|v| {
-                                        if v.is_null() {
-                                            Some(Value::Null)
-                                        } else {
-                                            row.try_get::<String, _>(i)
-                                                .map(Value::String)
-                                                .or_else(|_| row.try_get::<i64, _>(i).map(|n| json!(n)))
-                                                .or_else(|_| row.try_get::<f64, _>(i).map(|n| json!(n)))
-                                                .or_else(|_| row.try_get::<bool, _>(i).map(|b| json!(b)))
-                                                .ok()
-                                        }
-                                    })
-                                    .unwrap_or(Value::Null)
-                            })
+                        (0..columns.len())
+                            .map(|i| decode_postgres_cell(row, i, &type_names[i]))
                             .collect()
                     })
                     .collect();
@@ -412,6 +562,7 @@ async fn query_postgres(pool: &sqlx::PgPool, sql: &str) -> QueryResult {
                     rows: data,
                     error: None,
                     affected_rows: None,
+                    error_detail: None,
                 }
             }
             Err(e) => QueryResult {
@@ -419,6 +570,7 @@ async fn query_postgres(pool: &sqlx::PgPool, sql: &str) -> QueryResult {
                 rows: vec![],
                 error: Some(e.to_string()),
                 affected_rows: None,
+                error_detail: Some(database::classify_sqlx_error(&e)),
             },
         }
     } else {
@@ -428,23 +580,84 @@ async fn query_postgres(pool: &sqlx::PgPool, sql: &str) -> QueryResult {
                 rows: vec![],
                 error: None,
                 affected_rows: Some(result.rows_affected() as i64),
+                error_detail: None,
             },
             Err(e) => QueryResult {
                 columns: vec![],
                 rows: vec![],
                 error: Some(e.to_string()),
                 affected_rows: None,
+                error_detail: Some(database::classify_sqlx_error(&e)),
             },
         }
     }
 }
 
+// 按列的声明类型解码 Postgres 单元格：TIMESTAMPTZ/TIMESTAMP/DATE/TIME 转 RFC3339/ISO
+// 字符串，NUMERIC 用 rust_decimal 保精度转字符串，JSON/JSONB 原样传 Value，
+// BYTEA 转 base64，UUID 转规范字符串，其余类型沿用原有级联尝试
+fn decode_postgres_cell(row: &sqlx::postgres::PgRow, i: usize, type_name: &str) -> Value {
+    use sqlx::Row;
+
+    if row.try_get_raw(i).map(|v| v.is_null()).unwrap_or(true) {
+        return Value::Null;
+    }
+
+    let tn = type_name.to_ascii_uppercase();
+
+    if tn.contains("JSON") {
+        if let Ok(v) = row.try_get::<serde_json::Value, _>(i) {
+            return v;
+        }
+    }
+    if tn == "NUMERIC" {
+        if let Ok(d) = row.try_get::<rust_decimal::Decimal, _>(i) {
+            return Value::String(d.to_string());
+        }
+    }
+    if tn == "UUID" {
+        if let Ok(u) = row.try_get::<uuid::Uuid, _>(i) {
+            return Value::String(u.to_string());
+        }
+    }
+    if tn == "BYTEA" {
+        if let Ok(b) = row.try_get::<Vec<u8>, _>(i) {
+            return bytes_to_json(b);
+        }
+    }
+    if tn.contains("TIMESTAMPTZ") {
+        if let Ok(dt) = row.try_get::<chrono::DateTime<chrono::Utc>, _>(i) {
+            return Value::String(dt.to_rfc3339());
+        }
+    }
+    if tn.contains("TIMESTAMP") {
+        if let Ok(dt) = row.try_get::<chrono::NaiveDateTime, _>(i) {
+            return Value::String(dt.and_utc().to_rfc3339());
+        }
+    }
+    if tn == "DATE" {
+        if let Ok(d) = row.try_get::<chrono::NaiveDate, _>(i) {
+            return Value::String(d.to_string());
+        }
+    }
+    if tn.contains("TIME") {
+        if let Ok(t) = row.try_get::<chrono::NaiveTime, _>(i) {
+            return Value::String(t.to_string());
+        }
+    }
+
+    row.try_get::<String, _>(i)
+        .map(Value::String)
+        .or_else(|_| row.try_get::<i64, _>(i).map(|n| json!(n)))
+        .or_else(|_| row.try_get::<f64, _>(i).map(|n| json!(n)))
+        .or_else(|_| row.try_get::<bool, _>(i).map(|b| json!(b)))
+        .unwrap_or(Value::Null)
+}
+
 async fn query_sqlite(pool: &sqlx::SqlitePool, sql: &str) -> QueryResult {
     use sqlx::Row;
     
-    let sql_upper = sql.trim().to_uppercase();
-    let is_select = sql_upper.starts_with("SELECT") || 
-                    sql_upper.starts_with("PRAGMA");
+    let is_select = database::is_row_producing(sql);
 
     if is_select {
         match sqlx::query(sql).fetch_all(pool).await {
@@ -455,6 +668,7 @@ async fn query_sqlite(pool: &sqlx::SqlitePool, sql: &str) -> QueryResult {
                         rows: vec![],
                         error: None,
                         affected_rows: None,
+                        error_detail: None,
                     };
                 }
 
@@ -463,29 +677,17 @@ async fn query_sqlite(pool: &sqlx::SqlitePool, sql: &str) -> QueryResult {
                     .iter()
                     .map(|c| c.name().to_string())
                     .collect();
+                let type_names: Vec<String> = rows[0]
+                    .columns()
+                    .iter()
+                    .map(|c| c.type_info().name().to_string())
+                    .collect();
 
                 let data: Vec<Vec<Value>> = rows
                     .iter()
                     .map(|row| {
-                        columns
-                            .iter()
-                            .enumerate()
-                            .map(|(i, _)| {
-                                row.try_get_raw(i)
-                                    .ok()
-                                    .and_then(|v| {
-                                        if v.is_null() {
-                                            Some(Value::Null)
-                                        } else {
-                                            row.try_get::<String, _>(i)
-                                                .map(Value::String)
-                                                .or_else(|_| row.try_get::<i64, _>(i).map(|n| json!(n)))
-                                                .or_else(|_| row.try_get::<f64, _>(i).map(|n| json!(n)))
-                                                .ok()
-                                        }
-                                    })
-                                    .unwrap_or(Value::Null)
-                            })
+                        (0..columns.len())
+                            .map(|i| decode_sqlite_cell(row, i, &type_names[i]))
                             .collect()
                     })
                     .collect();
@@ -495,6 +697,7 @@ async fn query_sqlite(pool: &sqlx::SqlitePool, sql: &str) -> QueryResult {
                     rows: data,
                     error: None,
                     affected_rows: None,
+                    error_detail: None,
                 }
             }
             Err(e) => QueryResult {
@@ -502,6 +705,7 @@ async fn query_sqlite(pool: &sqlx::SqlitePool, sql: &str) -> QueryResult {
                 rows: vec![],
                 error: Some(e.to_string()),
                 affected_rows: None,
+                error_detail: Some(database::classify_sqlite_error(&e)),
             },
         }
     } else {
@@ -511,40 +715,63 @@ async fn query_sqlite(pool: &sqlx::SqlitePool, sql: &str) -> QueryResult {
                 rows: vec![],
                 error: None,
                 affected_rows: Some(result.rows_affected() as i64),
+                error_detail: None,
             },
             Err(e) => QueryResult {
                 columns: vec![],
                 rows: vec![],
                 error: Some(e.to_string()),
                 affected_rows: None,
+                error_detail: Some(database::classify_sqlite_error(&e)),
             },
         }
     }
 }
 
-async fn query_sqlserver(conn: &crate::database::SqlServerConnection, sql: &str) -> QueryResult {
-    use tiberius::Client;
-    use tokio::net::TcpStream;
-    use tokio_util::compat::TokioAsyncWriteCompatExt;
+// SQLite 是动态类型，声明的列类型只是一种"亲和性"提示：BLOB 按二进制取出转 base64，
+// NUMERIC/DECIMAL 尝试按 rust_decimal 保精度读取，DATETIME 尝试按 chrono 读取，
+// 其余情况沿用原有的 String/i64/f64 级联尝试
+fn decode_sqlite_cell(row: &sqlx::sqlite::SqliteRow, i: usize, type_name: &str) -> Value {
+    use sqlx::Row;
 
-    let tcp = match TcpStream::connect(conn.config.get_addr()).await {
-        Ok(t) => t,
-        Err(e) => return QueryResult {
-            columns: vec![],
-            rows: vec![],
-            error: Some(e.to_string()),
-            affected_rows: None,
-        },
-    };
-    tcp.set_nodelay(true).ok();
+    if row.try_get_raw(i).map(|v| v.is_null()).unwrap_or(true) {
+        return Value::Null;
+    }
 
-    let mut client = match Client::connect(conn.config.clone(), tcp.compat_write()).await {
+    let tn = type_name.to_ascii_uppercase();
+
+    if tn.contains("BLOB") {
+        if let Ok(b) = row.try_get::<Vec<u8>, _>(i) {
+            return bytes_to_json(b);
+        }
+    }
+    if tn.contains("DECIMAL") || tn.contains("NUMERIC") {
+        if let Ok(d) = row.try_get::<rust_decimal::Decimal, _>(i) {
+            return Value::String(d.to_string());
+        }
+    }
+    if tn.contains("DATETIME") || tn.contains("TIMESTAMP") {
+        if let Ok(dt) = row.try_get::<chrono::NaiveDateTime, _>(i) {
+            return Value::String(dt.and_utc().to_rfc3339());
+        }
+    }
+
+    row.try_get::<String, _>(i)
+        .map(Value::String)
+        .or_else(|_| row.try_get::<i64, _>(i).map(|n| json!(n)))
+        .or_else(|_| row.try_get::<f64, _>(i).map(|n| json!(n)))
+        .unwrap_or(Value::Null)
+}
+
+async fn query_sqlserver(conn: &crate::database::SqlServerConnection, sql: &str) -> QueryResult {
+    let mut client = match conn.pool.get().await {
         Ok(c) => c,
         Err(e) => return QueryResult {
             columns: vec![],
             rows: vec![],
             error: Some(e.to_string()),
             affected_rows: None,
+            error_detail: None,
         },
     };
 
@@ -567,6 +794,31 @@ async fn query_sqlserver(conn: &crate::database::SqlServerConnection, sql: &str)
                                 .or_else(|| row.try_get::<i32, _>(i).ok().flatten().map(|n| json!(n)))
                                 .or_else(|| row.try_get::<i64, _>(i).ok().flatten().map(|n| json!(n)))
                                 .or_else(|| row.try_get::<f64, _>(i).ok().flatten().map(|n| json!(n)))
+                                .or_else(|| row.try_get::<bool, _>(i).ok().flatten().map(|b| json!(b)))
+                                .or_else(|| {
+                                    row.try_get::<rust_decimal::Decimal, _>(i)
+                                        .ok()
+                                        .flatten()
+                                        .map(|d| Value::String(d.to_string()))
+                                })
+                                .or_else(|| {
+                                    row.try_get::<uuid::Uuid, _>(i)
+                                        .ok()
+                                        .flatten()
+                                        .map(|u| Value::String(u.to_string()))
+                                })
+                                .or_else(|| {
+                                    row.try_get::<chrono::NaiveDateTime, _>(i)
+                                        .ok()
+                                        .flatten()
+                                        .map(|dt| Value::String(dt.and_utc().to_rfc3339()))
+                                })
+                                .or_else(|| {
+                                    row.try_get::<&[u8], _>(i)
+                                        .ok()
+                                        .flatten()
+                                        .map(|b| bytes_to_json(b.to_vec()))
+                                })
                                 .unwrap_or(Value::Null)
                         })
                         .collect();
@@ -579,6 +831,7 @@ async fn query_sqlserver(conn: &crate::database::SqlServerConnection, sql: &str)
                 rows,
                 error: None,
                 affected_rows: None,
+                error_detail: None,
             }
         }
         Err(e) => QueryResult {
@@ -586,7 +839,325 @@ async fn query_sqlserver(conn: &crate::database::SqlServerConnection, sql: &str)
             rows: vec![],
             error: Some(e.to_string()),
             affected_rows: None,
+            error_detail: Some(database::classify_tiberius_error(&e)),
+        },
+    }
+}
+
+// 将一个 JSON 参数值绑定到 sqlx 查询的下一个占位符上（MySQL/SQLite 的 `?`、Postgres 的 `$n`）；`{"type":"bytes","b64":"..."}`
+// 约定用于传递二进制数据，其余类型按最接近的原生类型绑定
+fn bind_json_value<'q, DB>(
+    mut query: sqlx::query::Query<'q, DB, <DB as sqlx::database::HasArguments<'q>>::Arguments>,
+    value: &'q Value,
+) -> sqlx::query::Query<'q, DB, <DB as sqlx::database::HasArguments<'q>>::Arguments>
+where
+    DB: sqlx::Database,
+    i64: sqlx::Type<DB> + sqlx::Encode<'q, DB>,
+    f64: sqlx::Type<DB> + sqlx::Encode<'q, DB>,
+    bool: sqlx::Type<DB> + sqlx::Encode<'q, DB>,
+    String: sqlx::Type<DB> + sqlx::Encode<'q, DB>,
+    Vec<u8>: sqlx::Type<DB> + sqlx::Encode<'q, DB>,
+    sqlx::types::Null: sqlx::Type<DB> + sqlx::Encode<'q, DB>,
+{
+    match value {
+        // 绑定未定型的 NULL，而不是 Option::<i64>::None：后者会让 Postgres 把参数
+        // OID 声明为 int8，对非整数列（timestamp/bool/text 等）赋 NULL 时会被服务端
+        // 当类型不匹配拒绝；sqlx::types::Null 不声明具体类型，交给服务端按列推断
+        Value::Null => query.bind(sqlx::types::Null),
+        Value::Bool(b) => query.bind(*b),
+        Value::Number(n) if n.is_i64() || n.is_u64() => query.bind(n.as_i64().unwrap_or_default()),
+        Value::Number(n) => query.bind(n.as_f64().unwrap_or_default()),
+        Value::String(s) => query.bind(s.clone()),
+        Value::Object(obj) if obj.get("type").and_then(|t| t.as_str()) == Some("bytes") => {
+            let bytes = obj
+                .get("b64")
+                .and_then(|v| v.as_str())
+                .and_then(|b64| base64::engine::general_purpose::STANDARD.decode(b64).ok())
+                .unwrap_or_default();
+            query.bind(bytes)
+        }
+        other => query.bind(other.to_string()),
+    }
+}
+
+#[tauri::command]
+pub async fn db_query_params(id: String, sql: String, params: Vec<Value>) -> QueryResult {
+    let connections = CONNECTIONS.read();
+    let conn_info = match connections.get(&id) {
+        Some(c) => c.clone(),
+        None => return QueryResult {
+            columns: vec![],
+            rows: vec![],
+            error: Some("未连接".to_string()),
+            affected_rows: None,
+            error_detail: None,
         },
+    };
+    drop(connections);
+
+    match &conn_info.connection {
+        DbConnection::MySql(pool) => query_mysql_params(pool, &sql, &params).await,
+        DbConnection::Postgres(pool) => query_postgres_params(pool, &sql, &params).await,
+        DbConnection::Sqlite(pool) => query_sqlite_params(pool, &sql, &params).await,
+        DbConnection::SqlServer(conn) => query_sqlserver_params(conn, &sql, &params).await,
+    }
+}
+
+async fn query_mysql_params(pool: &sqlx::MySqlPool, sql: &str, params: &[Value]) -> QueryResult {
+    use sqlx::Row;
+
+    let is_select = database::is_row_producing(sql);
+
+    let mut query = sqlx::query(sql);
+    for param in params {
+        query = bind_json_value(query, param);
+    }
+
+    if is_select {
+        match query.fetch_all(pool).await {
+            Ok(rows) => {
+                if rows.is_empty() {
+                    return QueryResult { columns: vec![], rows: vec![], error: None, affected_rows: None, error_detail: None };
+                }
+                let columns: Vec<String> = rows[0].columns().iter().map(|c| c.name().to_string()).collect();
+                let data: Vec<Vec<Value>> = rows
+                    .iter()
+                    .map(|row| {
+                        columns
+                            .iter()
+                            .enumerate()
+                            .map(|(i, _)| {
+                                row.try_get_raw(i)
+                                    .ok()
+                                    .and_then(|v| {
+                                        if v.is_null() {
+                                            Some(Value::Null)
+                                        } else {
+                                            row.try_get::<String, _>(i)
+                                                .map(Value::String)
+                                                .or_else(|_| row.try_get::<i64, _>(i).map(|n| json!(n)))
+                                                .or_else(|_| row.try_get::<f64, _>(i).map(|n| json!(n)))
+                                                .or_else(|_| row.try_get::<bool, _>(i).map(|b| json!(b)))
+                                                .ok()
+                                        }
+                                    })
+                                    .unwrap_or(Value::Null)
+                            })
+                            .collect()
+                    })
+                    .collect();
+                QueryResult { columns, rows: data, error: None, affected_rows: None, error_detail: None }
+            }
+            Err(e) => {
+                let detail = database::classify_sqlx_error(&e);
+                QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) }
+            }
+        }
+    } else {
+        match query.execute(pool).await {
+            Ok(result) => QueryResult { columns: vec![], rows: vec![], error: None, affected_rows: Some(result.rows_affected() as i64), error_detail: None },
+            Err(e) => {
+                let detail = database::classify_sqlx_error(&e);
+                QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) }
+            }
+        }
+    }
+}
+
+async fn query_postgres_params(pool: &sqlx::PgPool, sql: &str, params: &[Value]) -> QueryResult {
+    use sqlx::Row;
+
+    let is_select = database::is_row_producing(sql);
+
+    let mut query = sqlx::query(sql);
+    for param in params {
+        query = bind_json_value(query, param);
+    }
+
+    if is_select {
+        match query.fetch_all(pool).await {
+            Ok(rows) => {
+                if rows.is_empty() {
+                    return QueryResult { columns: vec![], rows: vec![], error: None, affected_rows: None, error_detail: None };
+                }
+                let columns: Vec<String> = rows[0].columns().iter().map(|c| c.name().to_string()).collect();
+                let data: Vec<Vec<Value>> = rows
+                    .iter()
+                    .map(|row| {
+                        columns
+                            .iter()
+                            .enumerate()
+                            .map(|(i, _)| {
+                                row.try_get_raw(i)
+                                    .ok()
+                                    .and_then(|v| {
+                                        if v.is_null() {
+                                            Some(Value::Null)
+                                        } else {
+                                            row.try_get::<String, _>(i)
+                                                .map(Value::String)
+                                                .or_else(|_| row.try_get::<i64, _>(i).map(|n| json!(n)))
+                                                .or_else(|_| row.try_get::<f64, _>(i).map(|n| json!(n)))
+                                                .or_else(|_| row.try_get::<bool, _>(i).map(|b| json!(b)))
+                                                .ok()
+                                        }
+                                    })
+                                    .unwrap_or(Value::Null)
+                            })
+                            .collect()
+                    })
+                    .collect();
+                QueryResult { columns, rows: data, error: None, affected_rows: None, error_detail: None }
+            }
+            Err(e) => {
+                let detail = database::classify_sqlx_error(&e);
+                QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) }
+            }
+        }
+    } else {
+        match query.execute(pool).await {
+            Ok(result) => QueryResult { columns: vec![], rows: vec![], error: None, affected_rows: Some(result.rows_affected() as i64), error_detail: None },
+            Err(e) => {
+                let detail = database::classify_sqlx_error(&e);
+                QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) }
+            }
+        }
+    }
+}
+
+async fn query_sqlite_params(pool: &sqlx::SqlitePool, sql: &str, params: &[Value]) -> QueryResult {
+    use sqlx::Row;
+
+    let is_select = database::is_row_producing(sql);
+
+    let mut query = sqlx::query(sql);
+    for param in params {
+        query = bind_json_value(query, param);
+    }
+
+    if is_select {
+        match query.fetch_all(pool).await {
+            Ok(rows) => {
+                if rows.is_empty() {
+                    return QueryResult { columns: vec![], rows: vec![], error: None, affected_rows: None, error_detail: None };
+                }
+                let columns: Vec<String> = rows[0].columns().iter().map(|c| c.name().to_string()).collect();
+                let data: Vec<Vec<Value>> = rows
+                    .iter()
+                    .map(|row| {
+                        columns
+                            .iter()
+                            .enumerate()
+                            .map(|(i, _)| {
+                                row.try_get_raw(i)
+                                    .ok()
+                                    .and_then(|v| {
+                                        if v.is_null() {
+                                            Some(Value::Null)
+                                        } else {
+                                            row.try_get::<String, _>(i)
+                                                .map(Value::String)
+                                                .or_else(|_| row.try_get::<i64, _>(i).map(|n| json!(n)))
+                                                .or_else(|_| row.try_get::<f64, _>(i).map(|n| json!(n)))
+                                                .ok()
+                                        }
+                                    })
+                                    .unwrap_or(Value::Null)
+                            })
+                            .collect()
+                    })
+                    .collect();
+                QueryResult { columns, rows: data, error: None, affected_rows: None, error_detail: None }
+            }
+            Err(e) => {
+                let detail = database::classify_sqlite_error(&e);
+                QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) }
+            }
+        }
+    } else {
+        match query.execute(pool).await {
+            Ok(result) => QueryResult { columns: vec![], rows: vec![], error: None, affected_rows: Some(result.rows_affected() as i64), error_detail: None },
+            Err(e) => {
+                let detail = database::classify_sqlite_error(&e);
+                QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) }
+            }
+        }
+    }
+}
+
+fn bind_tiberius_param(query: &mut tiberius::Query<'_>, value: &Value) {
+    match value {
+        // tiberius/TDS 没有 sqlx::types::Null 那种未定型 NULL，参数必须带具体类型；
+        // 绑成 Option::<i64>::None 会把 NULL 声明成 INT，对非数值列赋值时要看 SQL Server
+        // 是否愿意做隐式转换。NVARCHAR(NULL) 在各列类型间的隐式转换覆盖面最广，
+        // 用它代替 INT 降低 NULL 写入非整数列时被拒绝的概率
+        Value::Null => query.bind(Option::<String>::None),
+        Value::Bool(b) => query.bind(*b),
+        Value::Number(n) if n.is_i64() || n.is_u64() => query.bind(n.as_i64().unwrap_or_default()),
+        Value::Number(n) => query.bind(n.as_f64().unwrap_or_default()),
+        Value::String(s) => query.bind(s.clone()),
+        Value::Object(obj) if obj.get("type").and_then(|t| t.as_str()) == Some("bytes") => {
+            let bytes = obj
+                .get("b64")
+                .and_then(|v| v.as_str())
+                .and_then(|b64| base64::engine::general_purpose::STANDARD.decode(b64).ok())
+                .unwrap_or_default();
+            query.bind(bytes)
+        }
+        other => query.bind(other.to_string()),
+    }
+}
+
+async fn query_sqlserver_params(conn: &crate::database::SqlServerConnection, sql: &str, params: &[Value]) -> QueryResult {
+    let mut client = match conn.pool.get().await {
+        Ok(c) => c,
+        Err(e) => return QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: None },
+    };
+
+    let mut query = tiberius::Query::new(sql);
+    for param in params {
+        bind_tiberius_param(&mut query, param);
+    }
+
+    match query.query(&mut client).await {
+        Ok(stream) => {
+            let mut columns = vec![];
+            let mut rows = vec![];
+
+            match stream.into_results().await {
+                Ok(result_sets) => {
+                    for result_set in result_sets {
+                        for row in result_set {
+                            if columns.is_empty() {
+                                columns = row.columns().iter().map(|c| c.name().to_string()).collect();
+                            }
+                            let row_data: Vec<Value> = (0..row.len())
+                                .map(|i| {
+                                    row.try_get::<&str, _>(i)
+                                        .ok()
+                                        .flatten()
+                                        .map(|s| Value::String(s.to_string()))
+                                        .or_else(|| row.try_get::<i32, _>(i).ok().flatten().map(|n| json!(n)))
+                                        .or_else(|| row.try_get::<i64, _>(i).ok().flatten().map(|n| json!(n)))
+                                        .or_else(|| row.try_get::<f64, _>(i).ok().flatten().map(|n| json!(n)))
+                                        .unwrap_or(Value::Null)
+                                })
+                                .collect();
+                            rows.push(row_data);
+                        }
+                    }
+                    QueryResult { columns, rows, error: None, affected_rows: None, error_detail: None }
+                }
+                Err(e) => {
+                    let detail = database::classify_tiberius_error(&e);
+                    QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) }
+                }
+            }
+        }
+        Err(e) => {
+            let detail = database::classify_tiberius_error(&e);
+            QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) }
+        }
     }
 }
 
@@ -620,21 +1191,11 @@ pub async fn db_get_databases(id: String) -> Vec<String> {
         DbConnection::SqlServer(conn) => {
             get_sqlserver_databases(conn).await
         }
-    }
-}
-
-async fn get_sqlserver_databases(conn: &crate::database::SqlServerConnection) -> Vec<String> {
-    use tiberius::Client;
-    use tokio::net::TcpStream;
-    use tokio_util::compat::TokioAsyncWriteCompatExt;
-
-    let tcp = match TcpStream::connect(conn.config.get_addr()).await {
-        Ok(t) => t,
-        Err(_) => return vec![],
-    };
-    tcp.set_nodelay(true).ok();
+    }
+}
 
-    let mut client = match Client::connect(conn.config.clone(), tcp.compat_write()).await {
+async fn get_sqlserver_databases(conn: &crate::database::SqlServerConnection) -> Vec<String> {
+    let mut client = match conn.pool.get().await {
         Ok(c) => c,
         Err(_) => return vec![],
     };
@@ -771,17 +1332,7 @@ async fn get_sqlite_tables(pool: &sqlx::SqlitePool) -> Vec<TableInfo> {
 }
 
 async fn get_sqlserver_tables(conn: &crate::database::SqlServerConnection, database: &str) -> Vec<TableInfo> {
-    use tiberius::Client;
-    use tokio::net::TcpStream;
-    use tokio_util::compat::TokioAsyncWriteCompatExt;
-
-    let tcp = match TcpStream::connect(conn.config.get_addr()).await {
-        Ok(t) => t,
-        Err(_) => return vec![],
-    };
-    tcp.set_nodelay(true).ok();
-
-    let mut client = match Client::connect(conn.config.clone(), tcp.compat_write()).await {
+    let mut client = match conn.pool.get().await {
         Ok(c) => c,
         Err(_) => return vec![],
     };
@@ -911,17 +1462,7 @@ async fn get_sqlite_columns(pool: &sqlx::SqlitePool, table: &str) -> Vec<ColumnI
 }
 
 async fn get_sqlserver_columns(conn: &crate::database::SqlServerConnection, database: &str, table: &str) -> Vec<ColumnInfo> {
-    use tiberius::Client;
-    use tokio::net::TcpStream;
-    use tokio_util::compat::TokioAsyncWriteCompatExt;
-
-    let tcp = match TcpStream::connect(conn.config.get_addr()).await {
-        Ok(t) => t,
-        Err(_) => return vec![],
-    };
-    tcp.set_nodelay(true).ok();
-
-    let mut client = match Client::connect(conn.config.clone(), tcp.compat_write()).await {
+    let mut client = match conn.pool.get().await {
         Ok(c) => c,
         Err(_) => return vec![],
     };
@@ -964,6 +1505,34 @@ pub async fn db_get_table_data(
     page: Option<i32>,
     page_size: Option<i32>,
 ) -> TableDataResult {
+    // 分页读取拿到空结果（没有列信息且总数为 0，连接断开时就是这种结果）时重连重试：
+    // 单页本身是有界的 LIMIT/OFFSET 查询，重连后重新拉取的是同一页。这里不区分出错原因，
+    // 表不存在之类非瞬时的空结果同样会按退避重试到次数用完
+    let mut attempt = 0;
+    loop {
+        let result = db_get_table_data_once(&id, &database, &table, page, page_size).await;
+        if !result.columns.is_empty() || result.total > 0 || attempt >= RECONNECT_MAX_ATTEMPTS {
+            return result;
+        }
+
+        backoff_delay(attempt).await;
+        attempt += 1;
+        if reconnect(&id).await.is_err() {
+            return result;
+        }
+    }
+}
+
+async fn db_get_table_data_once(
+    id: &str,
+    database: &str,
+    table: &str,
+    page: Option<i32>,
+    page_size: Option<i32>,
+) -> TableDataResult {
+    let id = id.to_string();
+    let database = database.to_string();
+    let table = table.to_string();
     let page = page.unwrap_or(1);
     let page_size = page_size.unwrap_or(100);
     let offset = (page - 1) * page_size;
@@ -1070,6 +1639,428 @@ pub async fn db_get_table_data(
     }
 }
 
+// 游标（keyset/seek）分页：深翻页场景下用 "WHERE order_col > :last ORDER BY order_col LIMIT n"
+// 代替 LIMIT/OFFSET，引擎不用再为了跳过前面的行扫描一遍。order_column 缺省取 db_get_columns
+// 标为 PRI 的主键列，没有标出主键时取第一列，
+// direction 为 "backward" 时反向取（< :last ORDER BY ... DESC），取回后再翻正序返回
+#[tauri::command]
+pub async fn db_get_table_data_keyset(
+    id: String,
+    database: String,
+    table: String,
+    order_column: Option<String>,
+    last_value: Option<Value>,
+    direction: Option<String>,
+    page_size: Option<i32>,
+) -> KeysetPageResult {
+    let page_size = page_size.unwrap_or(100).max(1);
+    let backward = direction.as_deref() == Some("backward");
+
+    let empty_result = |order_column: String| KeysetPageResult {
+        columns: vec![],
+        rows: vec![],
+        total: 0,
+        page_size,
+        order_column,
+        first_value: None,
+        last_value: None,
+        has_more: false,
+    };
+
+    let connections = CONNECTIONS.read();
+    let conn_info = match connections.get(&id) {
+        Some(c) => c.clone(),
+        None => return empty_result(order_column.unwrap_or_default()),
+    };
+    drop(connections);
+
+    let columns = db_get_columns(id.clone(), database.clone(), table.clone()).await;
+
+    let order_column = match order_column.filter(|c| !c.is_empty()) {
+        Some(c) => c,
+        None => columns
+            .iter()
+            .find(|c| c.key.as_deref() == Some("PRI"))
+            .or_else(|| columns.first())
+            .map(|c| c.name.clone())
+            .unwrap_or_default(),
+    };
+
+    if order_column.is_empty() {
+        return empty_result(order_column);
+    }
+
+    let quoted_table = quote_ident(&conn_info.connection, &table);
+    let quoted_col = quote_ident(&conn_info.connection, &order_column);
+    let (cmp, order_dir) = if backward { ("<", "DESC") } else { (">", "ASC") };
+    // 多取一行用来判断是否还有下一页，不用为此单独再发一次 COUNT 查询
+    let fetch_size = page_size + 1;
+
+    // MySQL 分支原先 COUNT 和分页 SELECT 是两次独立的 pool 操作（COUNT 直接 execute(pool)
+    // 发 USE、随后 fetch_one(pool) 发查询；分页 SELECT 又经 db_query_params 单独走
+    // query_mysql_params 对 pool 再 fetch_all），USE 和两条查询之间互不保证落在同一条
+    // 物理连接上，和 chunk2-1 里 db_update_row/db_delete_row 原来的坑同一类——可能悄悄
+    // 对错的库分页、甚至 db_backup/db_export_table 把别的库的数据当成目标库导出。这里
+    // 改成只 acquire 一次连接，USE、COUNT、分页 SELECT 依次在同一条连接上执行。
+    let (total, result) = match &conn_info.connection {
+        DbConnection::MySql(pool) => {
+            let count_sql = format!("SELECT COUNT(*) FROM {}", quoted_table);
+            let page_sql = match &last_value {
+                Some(_) => format!(
+                    "SELECT * FROM {} WHERE {} {} ? ORDER BY {} {} LIMIT {}",
+                    quoted_table, quoted_col, cmp, quoted_col, order_dir, fetch_size
+                ),
+                None => format!("SELECT * FROM {} ORDER BY {} {} LIMIT {}", quoted_table, quoted_col, order_dir, fetch_size),
+            };
+            let params = last_value.iter().cloned().collect::<Vec<_>>();
+            match query_mysql_keyset_scoped(pool, &database, &count_sql, &page_sql, &params).await {
+                Ok((total, result)) => (total, result),
+                Err(result) => (0, result),
+            }
+        }
+        DbConnection::Postgres(pool) => {
+            let total = sqlx::query_as::<_, (i64,)>(&format!("SELECT COUNT(*) FROM {}", quoted_table))
+                .fetch_one(pool)
+                .await
+                .map(|(c,)| c)
+                .unwrap_or(0);
+            let sql = match &last_value {
+                Some(_) => format!(
+                    "SELECT * FROM {} WHERE {} {} $1 ORDER BY {} {} LIMIT {}",
+                    quoted_table, quoted_col, cmp, quoted_col, order_dir, fetch_size
+                ),
+                None => format!("SELECT * FROM {} ORDER BY {} {} LIMIT {}", quoted_table, quoted_col, order_dir, fetch_size),
+            };
+            let params = last_value.iter().cloned().collect::<Vec<_>>();
+            (total, db_query_params(id.clone(), sql, params).await)
+        }
+        DbConnection::Sqlite(pool) => {
+            let total = sqlx::query_as::<_, (i64,)>(&format!("SELECT COUNT(*) FROM {}", quoted_table))
+                .fetch_one(pool)
+                .await
+                .map(|(c,)| c)
+                .unwrap_or(0);
+            let sql = match &last_value {
+                Some(_) => format!(
+                    "SELECT * FROM {} WHERE {} {} ? ORDER BY {} {} LIMIT {}",
+                    quoted_table, quoted_col, cmp, quoted_col, order_dir, fetch_size
+                ),
+                None => format!("SELECT * FROM {} ORDER BY {} {} LIMIT {}", quoted_table, quoted_col, order_dir, fetch_size),
+            };
+            let params = last_value.iter().cloned().collect::<Vec<_>>();
+            (total, db_query_params(id.clone(), sql, params).await)
+        }
+        DbConnection::SqlServer(_) => {
+            let total_sql = format!("USE [{}]; SELECT COUNT(*) FROM {}", database, quoted_table);
+            let total = db_query(id.clone(), total_sql)
+                .await
+                .rows
+                .first()
+                .and_then(|r| r.first())
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0);
+            let sql = match &last_value {
+                Some(_) => format!(
+                    "USE [{}]; SELECT TOP ({}) * FROM {} WHERE {} {} @p1 ORDER BY {} {}",
+                    database, fetch_size, quoted_table, quoted_col, cmp, quoted_col, order_dir
+                ),
+                None => format!(
+                    "USE [{}]; SELECT TOP ({}) * FROM {} ORDER BY {} {}",
+                    database, fetch_size, quoted_table, quoted_col, order_dir
+                ),
+            };
+            let params = last_value.iter().cloned().collect::<Vec<_>>();
+            (total, db_query_params(id.clone(), sql, params).await)
+        }
+    };
+
+    if result.error.is_some() {
+        let mut r = empty_result(order_column);
+        r.columns = columns;
+        return r;
+    }
+
+    let col_index = result.columns.iter().position(|c| c == &order_column);
+    let (rows, has_more, first_value, last_value_out) = finalize_keyset_page(result.rows, page_size, backward, col_index);
+
+    KeysetPageResult {
+        columns,
+        rows,
+        total,
+        page_size,
+        order_column,
+        first_value,
+        last_value: last_value_out,
+        has_more,
+    }
+}
+
+// MySQL/SQL Server 切库要单独发一条不带参数绑定的 USE 语句：一旦语句里带了待绑定的
+// 参数，sqlx 的 MySQL 驱动会走二进制预处理协议，而该协议一次只能 prepare 一条语句，
+// `USE db; UPDATE ...` 这种拼接会在 prepare 阶段就因为多出的 `;` 报语法错误。
+//
+// USE 和随后的语句必须发生在同一条物理连接上：之前的写法是 USE 单独取一次连接切库，
+// 下一次调用再单独取一次连接跑语句，两次 pool.acquire()/bb8::Pool::get() 并不保证
+// 拿到同一条连接——落到另一条连接上时，语句会悄悄对那条连接当时所在的库生效，而不是
+// 传入的 database，属于静默跑错库，比原来的语法错误更糟。因此改成按方言提供「USE +
+// 语句」绑在同一条连接上执行的版本，db_update_row/db_delete_row 都走这些版本。
+async fn query_mysql_use_and_params(pool: &sqlx::MySqlPool, database: &str, sql: &str, params: &[Value]) -> QueryResult {
+    let mut conn = match pool.acquire().await {
+        Ok(c) => c,
+        Err(e) => return QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: None },
+    };
+
+    let use_sql = format!("USE `{}`", database);
+    if let Err(e) = sqlx::query(&use_sql).execute(&mut *conn).await {
+        let detail = database::classify_sqlx_error(&e);
+        return QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) };
+    }
+
+    let mut query = sqlx::query(sql);
+    for param in params {
+        query = bind_json_value(query, param);
+    }
+
+    match query.execute(&mut *conn).await {
+        Ok(result) => QueryResult { columns: vec![], rows: vec![], error: None, affected_rows: Some(result.rows_affected() as i64), error_detail: None },
+        Err(e) => {
+            let detail = database::classify_sqlx_error(&e);
+            QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) }
+        }
+    }
+}
+
+async fn query_sqlserver_use_and_params(conn: &crate::database::SqlServerConnection, database: &str, sql: &str, params: &[Value]) -> QueryResult {
+    let mut client = match conn.pool.get().await {
+        Ok(c) => c,
+        Err(e) => return QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: None },
+    };
+
+    // USE 的结果流必须完整消费掉（into_results），TDS 是单条有序流协议，
+    // 留着没读完的流会让下一条 query() 在同一条连接上失败
+    let use_sql = format!("USE [{}]", database);
+    match client.simple_query(&use_sql).await {
+        Ok(stream) => {
+            if let Err(e) = stream.into_results().await {
+                let detail = database::classify_tiberius_error(&e);
+                return QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) };
+            }
+        }
+        Err(e) => {
+            let detail = database::classify_tiberius_error(&e);
+            return QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) };
+        }
+    }
+
+    let mut query = tiberius::Query::new(sql);
+    for param in params {
+        bind_tiberius_param(&mut query, param);
+    }
+
+    match query.query(&mut client).await {
+        Ok(stream) => match stream.into_results().await {
+            Ok(_) => QueryResult { columns: vec![], rows: vec![], error: None, affected_rows: None, error_detail: None },
+            Err(e) => {
+                let detail = database::classify_tiberius_error(&e);
+                QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) }
+            }
+        },
+        Err(e) => {
+            let detail = database::classify_tiberius_error(&e);
+            QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) }
+        }
+    }
+}
+
+// db_update_row/db_delete_row 的统一入口：MySQL/SQL Server 把 USE 和绑定参数的语句
+// 绑在同一条连接上执行；Postgres/SQLite 的库在连接建立时就固定，没有切库这回事，
+// 仍然走 db_query_params 各自取连接即可
+async fn execute_scoped_params(id: &str, conn: &DbConnection, database: &str, sql: String, params: Vec<Value>) -> QueryResult {
+    match conn {
+        DbConnection::MySql(pool) => query_mysql_use_and_params(pool, database, &sql, &params).await,
+        DbConnection::SqlServer(sc) => query_sqlserver_use_and_params(sc, database, &sql, &params).await,
+        DbConnection::Postgres(_) | DbConnection::Sqlite(_) => db_query_params(id.to_string(), sql, params).await,
+    }
+}
+
+// 订阅刷新（subscription.rs）也要在同一条连接上先 USE 再跑订阅的 SQL，原理同上；
+// 这里只处理 MySQL 这一种需要"USE + 查询" 拆成两步但又必须绑定同一连接的情形，
+// SQL Server 走 simple_query 本身支持一次发多条语句，拼接后单次调用即可，不受影响
+pub(crate) async fn query_mysql_scoped(pool: &sqlx::MySqlPool, database: &str, sql: &str) -> QueryResult {
+    use sqlx::Row;
+
+    let mut conn = match pool.acquire().await {
+        Ok(c) => c,
+        Err(e) => return QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: None },
+    };
+
+    let use_sql = format!("USE `{}`", database);
+    if let Err(e) = sqlx::query(&use_sql).execute(&mut *conn).await {
+        let detail = database::classify_sqlx_error(&e);
+        return QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) };
+    }
+
+    let is_select = database::is_row_producing(sql);
+    if is_select {
+        match sqlx::query(sql).fetch_all(&mut *conn).await {
+            Ok(rows) => {
+                if rows.is_empty() {
+                    return QueryResult { columns: vec![], rows: vec![], error: None, affected_rows: None, error_detail: None };
+                }
+                let columns: Vec<String> = rows[0].columns().iter().map(|c| c.name().to_string()).collect();
+                let type_names: Vec<String> = rows[0].columns().iter().map(|c| c.type_info().name().to_string()).collect();
+                let data: Vec<Vec<Value>> = rows
+                    .iter()
+                    .map(|row| (0..columns.len()).map(|i| decode_mysql_cell(row, i, &type_names[i])).collect())
+                    .collect();
+                QueryResult { columns, rows: data, error: None, affected_rows: None, error_detail: None }
+            }
+            Err(e) => {
+                let detail = database::classify_sqlx_error(&e);
+                QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) }
+            }
+        }
+    } else {
+        match sqlx::query(sql).execute(&mut *conn).await {
+            Ok(result) => QueryResult { columns: vec![], rows: vec![], error: None, affected_rows: Some(result.rows_affected() as i64), error_detail: None },
+            Err(e) => {
+                let detail = database::classify_sqlx_error(&e);
+                QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) }
+            }
+        }
+    }
+}
+
+// 每次按 fetch_size = page_size + 1 多取一行来判断是否还有下一页，再把多出的那一行
+// 截掉；反向翻页（backward）时分页 SQL 是按 order_column 倒序取的，截断之后要翻回
+// 正序再返回给前端。抽成纯函数方便覆盖边界情况（空结果、恰好一页、反向翻页）
+fn finalize_keyset_page(
+    mut rows: Vec<Vec<Value>>,
+    page_size: i32,
+    backward: bool,
+    col_index: Option<usize>,
+) -> (Vec<Vec<Value>>, bool, Option<Value>, Option<Value>) {
+    let has_more = rows.len() as i32 > page_size;
+    rows.truncate(page_size as usize);
+    if backward {
+        rows.reverse();
+    }
+    let first_value = col_index.and_then(|i| rows.first().and_then(|r| r.get(i)).cloned());
+    let last_value = col_index.and_then(|i| rows.last().and_then(|r| r.get(i)).cloned());
+    (rows, has_more, first_value, last_value)
+}
+
+#[cfg(test)]
+mod keyset_page_tests {
+    use super::*;
+
+    fn row(n: i64) -> Vec<Value> {
+        vec![json!(n)]
+    }
+
+    #[test]
+    fn no_more_pages_when_fetched_rows_fit_in_one_page() {
+        let rows = vec![row(1), row(2)];
+        let (rows, has_more, first, last) = finalize_keyset_page(rows, 2, false, Some(0));
+        assert_eq!(rows.len(), 2);
+        assert!(!has_more);
+        assert_eq!(first, Some(json!(1)));
+        assert_eq!(last, Some(json!(2)));
+    }
+
+    #[test]
+    fn extra_fetched_row_is_truncated_and_flags_has_more() {
+        // fetch_size = page_size + 1，多取的第 3 行只用来判断还有没有下一页，不应该出现在结果里
+        let rows = vec![row(1), row(2), row(3)];
+        let (rows, has_more, _, last) = finalize_keyset_page(rows, 2, false, Some(0));
+        assert_eq!(rows, vec![row(1), row(2)]);
+        assert!(has_more);
+        assert_eq!(last, Some(json!(2)));
+    }
+
+    #[test]
+    fn backward_page_is_reversed_back_to_ascending_order() {
+        // 反向翻页的 SQL 按 order_column DESC 取，截断后要翻回正序
+        let rows = vec![row(5), row(4), row(3)];
+        let (rows, has_more, first, last) = finalize_keyset_page(rows, 2, true, Some(0));
+        assert_eq!(rows, vec![row(4), row(5)]);
+        assert!(has_more);
+        assert_eq!(first, Some(json!(4)));
+        assert_eq!(last, Some(json!(5)));
+    }
+
+    #[test]
+    fn empty_result_has_no_more_pages_and_no_cursor_values() {
+        let (rows, has_more, first, last) = finalize_keyset_page(vec![], 2, false, Some(0));
+        assert!(rows.is_empty());
+        assert!(!has_more);
+        assert_eq!(first, None);
+        assert_eq!(last, None);
+    }
+
+    #[test]
+    fn missing_col_index_yields_no_cursor_values() {
+        let rows = vec![row(1), row(2)];
+        let (_, _, first, last) = finalize_keyset_page(rows, 2, false, None);
+        assert_eq!(first, None);
+        assert_eq!(last, None);
+    }
+}
+
+// db_get_table_data_keyset 的 MySQL 分支专用：acquire 一次连接，USE、COUNT、分页
+// SELECT 依次在这同一条连接上跑完，原理同 query_mysql_scoped；区别是这里要把 COUNT
+// 的结果也带出来，所以返回 (total, QueryResult) 而不是单个 QueryResult
+async fn query_mysql_keyset_scoped(
+    pool: &sqlx::MySqlPool,
+    database: &str,
+    count_sql: &str,
+    page_sql: &str,
+    params: &[Value],
+) -> Result<(i64, QueryResult), QueryResult> {
+    use sqlx::Row;
+
+    let mut conn = match pool.acquire().await {
+        Ok(c) => c,
+        Err(e) => return Err(QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: None }),
+    };
+
+    let use_sql = format!("USE `{}`", database);
+    if let Err(e) = sqlx::query(&use_sql).execute(&mut *conn).await {
+        let detail = database::classify_sqlx_error(&e);
+        return Err(QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) });
+    }
+
+    let total = sqlx::query_as::<_, (i64,)>(count_sql)
+        .fetch_one(&mut *conn)
+        .await
+        .map(|(c,)| c)
+        .unwrap_or(0);
+
+    let mut query = sqlx::query(page_sql);
+    for param in params {
+        query = bind_json_value(query, param);
+    }
+
+    match query.fetch_all(&mut *conn).await {
+        Ok(rows) => {
+            if rows.is_empty() {
+                return Ok((total, QueryResult { columns: vec![], rows: vec![], error: None, affected_rows: None, error_detail: None }));
+            }
+            let columns: Vec<String> = rows[0].columns().iter().map(|c| c.name().to_string()).collect();
+            let type_names: Vec<String> = rows[0].columns().iter().map(|c| c.type_info().name().to_string()).collect();
+            let data: Vec<Vec<Value>> = rows
+                .iter()
+                .map(|row| (0..columns.len()).map(|i| decode_mysql_cell(row, i, &type_names[i])).collect())
+                .collect();
+            Ok((total, QueryResult { columns, rows: data, error: None, affected_rows: None, error_detail: None }))
+        }
+        Err(e) => {
+            let detail = database::classify_sqlx_error(&e);
+            Err(QueryResult { columns: vec![], rows: vec![], error: Some(e.to_string()), affected_rows: None, error_detail: Some(detail) })
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn db_update_row(
     id: String,
@@ -1084,6 +2075,7 @@ pub async fn db_update_row(
         None => return CommandResult {
             success: false,
             message: "未连接".to_string(),
+            error_detail: None,
         },
     };
     drop(connections);
@@ -1096,83 +2088,79 @@ pub async fn db_update_row(
         return CommandResult {
             success: false,
             message: "参数错误".to_string(),
+            error_detail: None,
         };
     }
 
     let updates_obj = updates_obj.unwrap();
-    let pk_val = pk_val.unwrap();
-
-    let set_clause: Vec<String> = updates_obj
-        .iter()
-        .map(|(k, v)| {
-            let value = match v {
-                Value::Null => "NULL".to_string(),
-                Value::String(s) => format!("'{}'", s.replace("'", "''")),
-                Value::Number(n) => n.to_string(),
-                Value::Bool(b) => if *b { "1" } else { "0" }.to_string(),
-                _ => format!("'{}'", v.to_string().replace("'", "''")),
-            };
-            format!("`{}` = {}", k, value)
-        })
-        .collect();
-
-    let pk_value = match pk_val {
-        Value::String(s) => format!("'{}'", s.replace("'", "''")),
-        Value::Number(n) => n.to_string(),
-        _ => format!("'{}'", pk_val.to_string()),
+    let pk_val = pk_val.unwrap().clone();
+
+    let columns: Vec<&String> = updates_obj.keys().collect();
+    let mut params: Vec<Value> = updates_obj.values().cloned().collect();
+    params.push(pk_val);
+
+    // 标识符（库名/表名/列名）仍按方言拼接转义，但值一律走占位符绑定，避免拼接注入
+    let (set_clause, pk_placeholder) = match &conn_info.connection {
+        DbConnection::MySql(_) => (
+            columns.iter().map(|k| format!("`{}` = ?", k)).collect::<Vec<_>>().join(", "),
+            "?".to_string(),
+        ),
+        DbConnection::Postgres(_) => (
+            columns
+                .iter()
+                .enumerate()
+                .map(|(i, k)| format!("\"{}\" = ${}", k, i + 1))
+                .collect::<Vec<_>>()
+                .join(", "),
+            format!("${}", columns.len() + 1),
+        ),
+        DbConnection::Sqlite(_) => (
+            columns.iter().map(|k| format!("\"{}\" = ?", k)).collect::<Vec<_>>().join(", "),
+            "?".to_string(),
+        ),
+        DbConnection::SqlServer(_) => (
+            columns
+                .iter()
+                .enumerate()
+                .map(|(i, k)| format!("[{}] = @p{}", k, i + 1))
+                .collect::<Vec<_>>()
+                .join(", "),
+            format!("@p{}", columns.len() + 1),
+        ),
     };
 
+    // MySQL/SQL Server 绑定参数后走二进制预处理协议，一次只能 prepare 一条语句，
+    // 不能再把 `USE db;` 和语句拼在一起发，USE 由 execute_scoped_params 在同一条连接上单独先发
     let sql = match &conn_info.connection {
         DbConnection::MySql(_) => {
-            format!(
-                "USE `{}`; UPDATE `{}` SET {} WHERE `{}` = {}",
-                database,
-                table,
-                set_clause.join(", ").replace("`", "`"),
-                pk_col,
-                pk_value
-            )
+            format!("UPDATE `{}` SET {} WHERE `{}` = {}", table, set_clause, pk_col, pk_placeholder)
         }
         DbConnection::Postgres(_) => {
-            format!(
-                "UPDATE \"{}\" SET {} WHERE \"{}\" = {}",
-                table,
-                set_clause.join(", ").replace("`", "\""),
-                pk_col,
-                pk_value
-            )
+            format!("UPDATE \"{}\" SET {} WHERE \"{}\" = {}", table, set_clause, pk_col, pk_placeholder)
         }
         DbConnection::Sqlite(_) => {
-            format!(
-                "UPDATE \"{}\" SET {} WHERE \"{}\" = {}",
-                table,
-                set_clause.join(", ").replace("`", "\""),
-                pk_col,
-                pk_value
-            )
+            format!("UPDATE \"{}\" SET {} WHERE \"{}\" = {}", table, set_clause, pk_col, pk_placeholder)
         }
         DbConnection::SqlServer(_) => {
-            format!(
-                "USE [{}]; UPDATE [{}] SET {} WHERE [{}] = {}",
-                database,
-                table,
-                set_clause.join(", ").replace("`", "[").replace("]", "]"),
-                pk_col,
-                pk_value
-            )
+            format!("UPDATE [{}] SET {} WHERE [{}] = {}", table, set_clause, pk_col, pk_placeholder)
         }
     };
 
-    let result = db_query(id, sql).await;
+    let result = execute_scoped_params(&id, &conn_info.connection, &database, sql, params).await;
     if result.error.is_some() {
         CommandResult {
             success: false,
             message: result.error.unwrap(),
+            error_detail: result.error_detail,
         }
     } else {
+        let mut changed = std::collections::HashSet::new();
+        changed.insert(table.to_lowercase());
+        crate::subscription::notify_tables_changed(&id, &changed).await;
         CommandResult {
             success: true,
             message: format!("更新成功，影响 {} 行", result.affected_rows.unwrap_or(0)),
+            error_detail: None,
         }
     }
 }
@@ -1190,6 +2178,7 @@ pub async fn db_delete_row(
         None => return CommandResult {
             success: false,
             message: "未连接".to_string(),
+            error_detail: None,
         },
     };
     drop(connections);
@@ -1201,51 +2190,207 @@ pub async fn db_delete_row(
         return CommandResult {
             success: false,
             message: "参数错误".to_string(),
+            error_detail: None,
         };
     }
 
-    let pk_val = pk_val.unwrap();
-    let pk_value = match pk_val {
-        Value::String(s) => format!("'{}'", s.replace("'", "''")),
-        Value::Number(n) => n.to_string(),
-        _ => format!("'{}'", pk_val.to_string()),
+    let pk_val = pk_val.unwrap().clone();
+    let params = vec![pk_val];
+
+    // 标识符仍按方言拼接转义，主键值走占位符绑定
+    let pk_placeholder = match &conn_info.connection {
+        DbConnection::MySql(_) | DbConnection::Sqlite(_) => "?".to_string(),
+        DbConnection::Postgres(_) => "$1".to_string(),
+        DbConnection::SqlServer(_) => "@p1".to_string(),
     };
 
+    // USE 不能和绑定参数的语句拼在一条里发给 MySQL/SQL Server，原因同 db_update_row
     let sql = match &conn_info.connection {
         DbConnection::MySql(_) => {
-            format!("USE `{}`; DELETE FROM `{}` WHERE `{}` = {}", database, table, pk_col, pk_value)
+            format!("DELETE FROM `{}` WHERE `{}` = {}", table, pk_col, pk_placeholder)
         }
         DbConnection::Postgres(_) => {
-            format!("DELETE FROM \"{}\" WHERE \"{}\" = {}", table, pk_col, pk_value)
+            format!("DELETE FROM \"{}\" WHERE \"{}\" = {}", table, pk_col, pk_placeholder)
         }
         DbConnection::Sqlite(_) => {
-            format!("DELETE FROM \"{}\" WHERE \"{}\" = {}", table, pk_col, pk_value)
+            format!("DELETE FROM \"{}\" WHERE \"{}\" = {}", table, pk_col, pk_placeholder)
         }
         DbConnection::SqlServer(_) => {
-            format!("USE [{}]; DELETE FROM [{}] WHERE [{}] = {}", database, table, pk_col, pk_value)
+            format!("DELETE FROM [{}] WHERE [{}] = {}", table, pk_col, pk_placeholder)
         }
     };
 
-    let result = db_query(id, sql).await;
+    let result = execute_scoped_params(&id, &conn_info.connection, &database, sql, params).await;
     if result.error.is_some() {
         CommandResult {
             success: false,
             message: result.error.unwrap(),
+            error_detail: result.error_detail,
         }
     } else {
+        let mut changed = std::collections::HashSet::new();
+        changed.insert(table.to_lowercase());
+        crate::subscription::notify_tables_changed(&id, &changed).await;
         CommandResult {
             success: true,
             message: format!("删除成功，影响 {} 行", result.affected_rows.unwrap_or(0)),
+            error_detail: None,
+        }
+    }
+}
+
+// 导出/备份按固定批大小分页拉取，避免大表一次性读入内存
+const EXPORT_BATCH_SIZE: i32 = 1000;
+
+fn quote_ident(conn: &DbConnection, ident: &str) -> String {
+    match conn {
+        DbConnection::MySql(_) => format!("`{}`", ident),
+        DbConnection::Postgres(_) | DbConnection::Sqlite(_) => format!("\"{}\"", ident),
+        DbConnection::SqlServer(_) => format!("[{}]", ident),
+    }
+}
+
+// 导出为 SQL dump 时把一个单元格的值渲染成字面量；二进制列走 bind_json_value 同款的
+// `{"type":"bytes","b64":"..."}` 约定，按方言转成对应的二进制字面量语法，不能直接当字符串拼
+fn sql_literal(conn: &DbConnection, value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(b) => if *b { "1" } else { "0" }.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => format!("'{}'", s.replace('\'', "''")),
+        Value::Object(obj) if obj.get("type").and_then(|t| t.as_str()) == Some("bytes") => {
+            let bytes = obj
+                .get("b64")
+                .and_then(|v| v.as_str())
+                .and_then(|b64| base64::engine::general_purpose::STANDARD.decode(b64).ok())
+                .unwrap_or_default();
+            let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            match conn {
+                DbConnection::MySql(_) | DbConnection::Sqlite(_) => format!("X'{}'", hex),
+                DbConnection::SqlServer(_) => format!("0x{}", hex),
+                DbConnection::Postgres(_) => format!("decode('{}', 'hex')", hex),
+            }
         }
+        other => format!("'{}'", other.to_string().replace('\'', "''")),
+    }
+}
+
+fn csv_escape(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') || s.contains('\r') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn value_to_csv_field(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => csv_escape(s),
+        other => csv_escape(&other.to_string()),
     }
 }
 
 #[tauri::command]
 pub async fn db_backup(id: String, database: String) -> CommandResult {
-    // 简化版备份 - 导出 SQL
+    let connections = CONNECTIONS.read();
+    let conn_info = match connections.get(&id) {
+        Some(c) => c.clone(),
+        None => return CommandResult { success: false, message: "未连接".to_string(), error_detail: None },
+    };
+    drop(connections);
+
+    let path = match rfd::FileDialog::new()
+        .add_filter("SQL 文件", &["sql"])
+        .set_file_name(&format!("{}.sql", database))
+        .save_file()
+    {
+        Some(p) => p,
+        None => return CommandResult { success: false, message: "用户取消".to_string(), error_detail: None },
+    };
+
+    let mut file = match std::fs::File::create(&path) {
+        Ok(f) => f,
+        Err(e) => return CommandResult { success: false, message: e.to_string(), error_detail: None },
+    };
+
+    use std::io::Write;
+    let tables = db_get_tables(id.clone(), database.clone()).await;
+    let mut total_rows = 0i64;
+    let mut table_count = 0usize;
+
+    for table_info in tables.iter().filter(|t| !t.is_view) {
+        let table = &table_info.name;
+        let columns = db_get_columns(id.clone(), database.clone(), table.clone()).await;
+        if columns.is_empty() {
+            continue;
+        }
+        table_count += 1;
+
+        let quoted_table = quote_ident(&conn_info.connection, table);
+        let col_defs = columns
+            .iter()
+            .map(|c| {
+                let null = if c.nullable { "" } else { " NOT NULL" };
+                format!("{} {}{}", quote_ident(&conn_info.connection, &c.name), c.data_type, null)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if let Err(e) = writeln!(file, "CREATE TABLE {} ({});", quoted_table, col_defs) {
+            return CommandResult { success: false, message: e.to_string(), error_detail: None };
+        }
+
+        let quoted_cols = columns
+            .iter()
+            .map(|c| quote_ident(&conn_info.connection, &c.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // 按游标分页（同 db_get_table_data_keyset）而非 LIMIT/OFFSET 拉取：没有 ORDER BY
+        // 锚定的 OFFSET 翻页不保证相邻两页精确衔接，大表备份可能重复或漏行；游标分页按
+        // order_column 定序，页与页之间严格靠上一页的 last_value 衔接。分页本身的 MySQL
+        // 切库一致性问题在 db_get_table_data_keyset 内部解决（见 chunk2-4），这里直接
+        // 拿到的就是已经绑定同一条连接、对得上 database 的结果，不需要重复处理
+        let mut last_value: Option<Value> = None;
+        loop {
+            let data = db_get_table_data_keyset(
+                id.clone(),
+                database.clone(),
+                table.clone(),
+                None,
+                last_value.clone(),
+                None,
+                Some(EXPORT_BATCH_SIZE),
+            )
+            .await;
+            if data.rows.is_empty() {
+                break;
+            }
+
+            for row in &data.rows {
+                let values = row.iter().map(|v| sql_literal(&conn_info.connection, v)).collect::<Vec<_>>().join(", ");
+                if let Err(e) = writeln!(file, "INSERT INTO {} ({}) VALUES ({});", quoted_table, quoted_cols, values) {
+                    return CommandResult { success: false, message: e.to_string(), error_detail: None };
+                }
+                total_rows += 1;
+            }
+
+            if !data.has_more {
+                break;
+            }
+            last_value = data.last_value;
+        }
+
+        if let Err(e) = writeln!(file) {
+            return CommandResult { success: false, message: e.to_string(), error_detail: None };
+        }
+    }
+
     CommandResult {
-        success: false,
-        message: "备份功能开发中".to_string(),
+        success: true,
+        message: format!("备份完成，共 {} 张表、{} 行，已保存到 {}", table_count, total_rows, path.to_string_lossy()),
+        error_detail: None,
     }
 }
 
@@ -1256,10 +2401,121 @@ pub async fn db_export_table(
     table: String,
     format: String,
 ) -> CommandResult {
-    // 简化版导出
+    let connections = CONNECTIONS.read();
+    let conn_info = match connections.get(&id) {
+        Some(c) => c.clone(),
+        None => return CommandResult { success: false, message: "未连接".to_string(), error_detail: None },
+    };
+    drop(connections);
+
+    let ext = match format.as_str() {
+        "json" => "json",
+        "sql" => "sql",
+        _ => "csv",
+    };
+
+    let path = match rfd::FileDialog::new()
+        .set_file_name(&format!("{}.{}", table, ext))
+        .save_file()
+    {
+        Some(p) => p,
+        None => return CommandResult { success: false, message: "用户取消".to_string(), error_detail: None },
+    };
+
+    let mut file = match std::fs::File::create(&path) {
+        Ok(f) => f,
+        Err(e) => return CommandResult { success: false, message: e.to_string(), error_detail: None },
+    };
+
+    use std::io::Write;
+    let columns = db_get_columns(id.clone(), database.clone(), table.clone()).await;
+    let column_names: Vec<String> = columns.iter().map(|c| c.name.clone()).collect();
+    let quoted_table = quote_ident(&conn_info.connection, &table);
+    let quoted_cols = column_names
+        .iter()
+        .map(|c| quote_ident(&conn_info.connection, c))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if format == "json" {
+        if let Err(e) = write!(file, "[") {
+            return CommandResult { success: false, message: e.to_string(), error_detail: None };
+        }
+    } else if format == "csv" {
+        let header = column_names.iter().map(|c| csv_escape(c)).collect::<Vec<_>>().join(",");
+        if let Err(e) = writeln!(file, "{}", header) {
+            return CommandResult { success: false, message: e.to_string(), error_detail: None };
+        }
+    }
+
+    // 同 db_backup：改走游标分页，避免 LIMIT/OFFSET 在无 ORDER BY 时重复/漏行
+    let mut last_value: Option<Value> = None;
+    let mut total_rows = 0i64;
+    let mut first_row = true;
+
+    loop {
+        let data = db_get_table_data_keyset(
+            id.clone(),
+            database.clone(),
+            table.clone(),
+            None,
+            last_value.clone(),
+            None,
+            Some(EXPORT_BATCH_SIZE),
+        )
+        .await;
+        if data.rows.is_empty() {
+            break;
+        }
+
+        for row in &data.rows {
+            match format.as_str() {
+                "json" => {
+                    let obj: serde_json::Map<String, Value> =
+                        column_names.iter().cloned().zip(row.iter().cloned()).collect();
+                    let line = serde_json::to_string(&Value::Object(obj)).unwrap_or_default();
+                    let prefix = if first_row { "" } else { "," };
+                    if let Err(e) = write!(file, "{}{}", prefix, line) {
+                        return CommandResult { success: false, message: e.to_string(), error_detail: None };
+                    }
+                }
+                "sql" => {
+                    let values = row.iter().map(|v| sql_literal(&conn_info.connection, v)).collect::<Vec<_>>().join(", ");
+                    if let Err(e) = writeln!(
+                        file,
+                        "INSERT INTO {} ({}) VALUES ({});",
+                        quoted_table, quoted_cols, values
+                    ) {
+                        return CommandResult { success: false, message: e.to_string(), error_detail: None };
+                    }
+                }
+                _ => {
+                    let line = row.iter().map(value_to_csv_field).collect::<Vec<_>>().join(",");
+                    if let Err(e) = writeln!(file, "{}", line) {
+                        return CommandResult { success: false, message: e.to_string(), error_detail: None };
+                    }
+                }
+            }
+            first_row = false;
+            total_rows += 1;
+        }
+
+        if !data.has_more {
+            break;
+        }
+        last_value = data.last_value;
+    }
+
+    if format == "json" {
+        if let Err(e) = write!(file, "]") {
+            return CommandResult { success: false, message: e.to_string(), error_detail: None };
+        }
+    }
+
     CommandResult {
-        success: false,
-        message: "导出功能开发中".to_string(),
+        success: true,
+        message: format!("导出成功，共 {} 行，已保存到 {}", total_rows, path.to_string_lossy()),
+        error_detail: None,
     }
 }
 
@@ -1271,10 +2527,12 @@ pub async fn config_save(connections: Vec<ConnectionConfig>) -> CommandResult {
         Ok(_) => CommandResult {
             success: true,
             message: "保存成功".to_string(),
+            error_detail: None,
         },
         Err(e) => CommandResult {
             success: false,
             message: e.to_string(),
+            error_detail: None,
         },
     }
 }
@@ -1289,6 +2547,7 @@ pub async fn config_export(connections: Vec<ConnectionConfig>, format: String) -
     CommandResult {
         success: false,
         message: "导出功能开发中".to_string(),
+        error_detail: None,
     }
 }
 
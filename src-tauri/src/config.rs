@@ -1,4 +1,5 @@
 use crate::database::ConnectionConfig;
+use crate::secrets::{self, SecretField};
 use std::fs;
 use std::path::PathBuf;
 
@@ -6,14 +7,51 @@ fn get_config_path() -> PathBuf {
     let config_dir = dirs::config_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("easysql");
-    
+
     fs::create_dir_all(&config_dir).ok();
     config_dir.join("connections.json")
 }
 
+// 三个需要托管到密钥链的字段
+const SECRET_FIELDS: [SecretField; 3] = [SecretField::Password, SecretField::SshPassword, SecretField::SshKey];
+
+// 将连接中的密码/SSH 密码/SSH 私钥写入密钥链，返回一份字段已清空的副本用于落盘
+fn strip_secrets(connection: &ConnectionConfig) -> ConnectionConfig {
+    let mut sanitized = connection.clone();
+
+    let _ = secrets::store(&connection.id, SecretField::Password, &connection.password);
+    sanitized.password = String::new();
+
+    if let Some(ssh_password) = &connection.ssh_password {
+        let _ = secrets::store(&connection.id, SecretField::SshPassword, ssh_password);
+        sanitized.ssh_password = None;
+    }
+    if let Some(ssh_key) = &connection.ssh_key {
+        let _ = secrets::store(&connection.id, SecretField::SshKey, ssh_key);
+        sanitized.ssh_key = None;
+    }
+
+    sanitized.secrets_in_keychain = Some(true);
+    sanitized
+}
+
+// 从密钥链取回密码/SSH 密码/SSH 私钥，填回内存中的连接配置供实际连接使用
+fn rehydrate_secrets(connection: &mut ConnectionConfig) {
+    if let Some(password) = secrets::load(&connection.id, SecretField::Password) {
+        connection.password = password;
+    }
+    if connection.ssh_password.is_none() {
+        connection.ssh_password = secrets::load(&connection.id, SecretField::SshPassword);
+    }
+    if connection.ssh_key.is_none() {
+        connection.ssh_key = secrets::load(&connection.id, SecretField::SshKey);
+    }
+}
+
 pub fn save_connections(connections: &[ConnectionConfig]) -> Result<(), std::io::Error> {
     let path = get_config_path();
-    let json = serde_json::to_string_pretty(connections)
+    let sanitized: Vec<ConnectionConfig> = connections.iter().map(strip_secrets).collect();
+    let json = serde_json::to_string_pretty(&sanitized)
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
     fs::write(path, json)
 }
@@ -23,9 +61,23 @@ pub fn load_connections() -> Result<Vec<ConnectionConfig>, std::io::Error> {
     if !path.exists() {
         return Ok(vec![]);
     }
-    
+
     let content = fs::read_to_string(path)?;
-    serde_json::from_str(&content)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    let mut connections: Vec<ConnectionConfig> = serde_json::from_str(&content)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+    // 旧版本以明文保存密码，首次加载时迁移进密钥链并重写配置文件
+    let mut migrated = false;
+    for connection in connections.iter_mut() {
+        if connection.secrets_in_keychain != Some(true) {
+            migrated = true;
+        }
+        rehydrate_secrets(connection);
+    }
+    if migrated {
+        let _ = save_connections(&connections);
+    }
+
+    Ok(connections)
 }
 
@@ -2,13 +2,16 @@ use once_cell::sync::Lazy;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum DbError {
+    // 第二个字段携带分类后的结构化详情（TLS 协商失败、鉴权被拒等），拿不到底层
+    // sqlx/tiberius 错误类型的场景（比如 TCP 连接本身失败、证书文件读写失败）留空
     #[error("连接失败: {0}")]
-    ConnectionError(String),
+    ConnectionError(String, Option<StructuredError>),
     #[error("查询失败: {0}")]
     QueryError(String),
     #[error("未连接")]
@@ -19,6 +22,271 @@ pub enum DbError {
     SshError(String),
 }
 
+impl DbError {
+    pub fn structured_detail(&self) -> Option<StructuredError> {
+        match self {
+            DbError::ConnectionError(_, detail) => detail.clone(),
+            _ => None,
+        }
+    }
+}
+
+// 判断一段错误文本是否代表瞬时性网络故障（连接被重置、管道损坏、超时、隧道断开等），
+// 这类错误值得重建连接后重试；语法错误、约束冲突等则应直接透传给前端
+pub fn is_transient_error_text(message: &str) -> bool {
+    let m = message.to_lowercase();
+    const MARKERS: [&str; 12] = [
+        "connection reset",
+        "broken pipe",
+        "timed out",
+        "timeout",
+        "connection closed",
+        "connection refused",
+        "connection aborted",
+        "os error 104",
+        "os error 32",
+        "os error 110",
+        "tunnel",
+        "unexpected eof",
+    ];
+    MARKERS.iter().any(|marker| m.contains(marker))
+}
+
+// 跳过前导空白和注释（-- 行注释、/* */ 块注释），返回第一个真正的关键字起始位置
+fn skip_leading_trivia(sql: &str) -> &str {
+    let mut s = sql;
+    loop {
+        let trimmed = s.trim_start();
+        if let Some(rest) = trimmed.strip_prefix("--") {
+            s = rest.splitn(2, '\n').nth(1).unwrap_or("");
+            continue;
+        }
+        if let Some(rest) = trimmed.strip_prefix("/*") {
+            match rest.find("*/") {
+                Some(end) => {
+                    s = &rest[end + 2..];
+                    continue;
+                }
+                None => return "",
+            }
+        }
+        return trimmed;
+    }
+}
+
+const ROW_PRODUCING_PREFIXES: [&str; 6] = ["WITH", "SELECT", "SHOW", "PRAGMA", "EXPLAIN", "DESCRIBE"];
+
+// 判断一条 SQL 语句是否会产生结果集：先跳过前导注释/空白再看首个关键字
+// （WITH 覆盖 CTE，SHOW/PRAGMA/EXPLAIN/DESCRIBE 是各后端的内省语句），
+// 否则退化为扫描顶层是否带 RETURNING 子句（Postgres/SQLite 的 DML RETURNING）
+pub fn is_row_producing(sql: &str) -> bool {
+    let trimmed = skip_leading_trivia(sql);
+    let prefix_upper = trimmed.as_bytes()[..trimmed.len().min(16)].to_ascii_uppercase();
+    let prefix_upper = String::from_utf8_lossy(&prefix_upper);
+    if ROW_PRODUCING_PREFIXES.iter().any(|kw| prefix_upper.starts_with(kw)) {
+        return true;
+    }
+    contains_top_level_keyword(trimmed, "RETURNING")
+}
+
+// 在忽略单引号/双引号/反引号内容的前提下，判断语句顶层是否出现某个关键字
+fn contains_top_level_keyword(sql: &str, keyword: &str) -> bool {
+    let upper = sql.to_ascii_uppercase();
+    let bytes = upper.as_bytes();
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut in_backtick = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if in_single {
+            if c == b'\'' { in_single = false; }
+            i += 1;
+            continue;
+        }
+        if in_double {
+            if c == b'"' { in_double = false; }
+            i += 1;
+            continue;
+        }
+        if in_backtick {
+            if c == b'`' { in_backtick = false; }
+            i += 1;
+            continue;
+        }
+        match c {
+            b'\'' => { in_single = true; i += 1; continue; }
+            b'"' => { in_double = true; i += 1; continue; }
+            b'`' => { in_backtick = true; i += 1; continue; }
+            _ => {}
+        }
+        if upper[i..].starts_with(keyword) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+// 识别形如 $$ 或 $tag$ 的 Postgres 美元引号起始标记，返回完整标记（含两端 $）
+fn scan_dollar_tag(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    if bytes.first() != Some(&b'$') {
+        return None;
+    }
+    let mut end = 1;
+    while end < bytes.len() && (bytes[end].is_ascii_alphanumeric() || bytes[end] == b'_') {
+        end += 1;
+    }
+    if bytes.get(end) == Some(&b'$') {
+        Some(s[..=end].to_string())
+    } else {
+        None
+    }
+}
+
+// 按顶层分号切分多语句脚本：单引号/双引号/反引号字符串与标识符、行注释/块注释、
+// 以及 Postgres 美元引号函数体（$$...$$ 或 $tag$...$tag$）内部的分号不作为分隔符
+pub fn split_statements(script: &str) -> Vec<String> {
+    let bytes = script.as_bytes();
+    let mut statements = Vec::new();
+    let mut start = 0usize;
+    let mut i = 0usize;
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut in_backtick = false;
+    let mut dollar_tag: Option<String> = None;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+
+        if let Some(tag) = &dollar_tag {
+            if c == b'$' && script[i..].starts_with(tag.as_str()) {
+                i += tag.len();
+                dollar_tag = None;
+            } else {
+                i += 1;
+            }
+            continue;
+        }
+        if in_single {
+            if c == b'\'' { in_single = false; }
+            i += 1;
+            continue;
+        }
+        if in_double {
+            if c == b'"' { in_double = false; }
+            i += 1;
+            continue;
+        }
+        if in_backtick {
+            if c == b'`' { in_backtick = false; }
+            i += 1;
+            continue;
+        }
+        if c == b'-' && bytes.get(i + 1) == Some(&b'-') {
+            while i < bytes.len() && bytes[i] != b'\n' { i += 1; }
+            continue;
+        }
+        if c == b'/' && bytes.get(i + 1) == Some(&b'*') {
+            i += 2;
+            while i + 1 < bytes.len() && !(bytes[i] == b'*' && bytes[i + 1] == b'/') { i += 1; }
+            i = (i + 2).min(bytes.len());
+            continue;
+        }
+
+        match c {
+            b'\'' => { in_single = true; i += 1; continue; }
+            b'"' => { in_double = true; i += 1; continue; }
+            b'`' => { in_backtick = true; i += 1; continue; }
+            b'$' => {
+                if let Some(tag) = scan_dollar_tag(&script[i..]) {
+                    i += tag.len();
+                    dollar_tag = Some(tag);
+                    continue;
+                }
+            }
+            b';' => {
+                let stmt = script[start..i].trim();
+                if !stmt.is_empty() {
+                    statements.push(stmt.to_string());
+                }
+                i += 1;
+                start = i;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let tail = script[start..].trim();
+    if !tail.is_empty() {
+        statements.push(tail.to_string());
+    }
+
+    statements
+}
+
+#[cfg(test)]
+mod split_statements_tests {
+    use super::*;
+
+    #[test]
+    fn splits_simple_statements() {
+        let stmts = split_statements("SELECT 1; SELECT 2;");
+        assert_eq!(stmts, vec!["SELECT 1", "SELECT 2"]);
+    }
+
+    #[test]
+    fn ignores_semicolon_inside_quotes_and_backticks() {
+        let stmts = split_statements("INSERT INTO t(`a;b`) VALUES ('x;y'); SELECT 1;");
+        assert_eq!(stmts, vec!["INSERT INTO t(`a;b`) VALUES ('x;y')", "SELECT 1"]);
+    }
+
+    #[test]
+    fn ignores_semicolon_inside_line_and_block_comments() {
+        let stmts = split_statements("SELECT 1; -- foo; bar\nSELECT 2; /* a;b */ SELECT 3;");
+        assert_eq!(stmts, vec!["SELECT 1", "SELECT 2", "SELECT 3"]);
+    }
+
+    #[test]
+    fn ignores_semicolon_inside_dollar_quoted_body() {
+        let stmts = split_statements("CREATE FUNCTION f() RETURNS int AS $$ BEGIN RETURN 1; END; $$ LANGUAGE plpgsql;");
+        assert_eq!(stmts.len(), 1);
+    }
+
+    #[test]
+    fn keeps_trailing_statement_without_semicolon() {
+        let stmts = split_statements("SELECT 1;SELECT 2");
+        assert_eq!(stmts, vec!["SELECT 1", "SELECT 2"]);
+    }
+
+    #[test]
+    fn empty_script_produces_no_statements() {
+        assert!(split_statements("   ").is_empty());
+    }
+
+    #[test]
+    fn is_row_producing_recognizes_select_and_cte() {
+        assert!(is_row_producing("SELECT * FROM t"));
+        assert!(is_row_producing("  -- comment\nWITH x AS (SELECT 1) SELECT * FROM x"));
+        assert!(is_row_producing("SHOW TABLES"));
+    }
+
+    #[test]
+    fn is_row_producing_recognizes_returning_clause() {
+        assert!(is_row_producing("DELETE FROM t WHERE id = 1 RETURNING id"));
+        assert!(!is_row_producing("DELETE FROM t WHERE name = 'RETURNING'"));
+    }
+
+    #[test]
+    fn is_row_producing_false_for_plain_dml() {
+        assert!(!is_row_producing("UPDATE t SET a = 1"));
+        assert!(!is_row_producing("INSERT INTO t VALUES (1)"));
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConnectionConfig {
@@ -37,6 +305,137 @@ pub struct ConnectionConfig {
     pub ssh_user: Option<String>,
     pub ssh_password: Option<String>,
     pub ssh_key: Option<String>,
+    // 私钥口令（加密私钥时需要）
+    pub ssh_key_passphrase: Option<String>,
+    // 直接以内容（而非文件路径）提供的私钥，例如粘贴进来的 PEM
+    pub ssh_key_data: Option<String>,
+    // 使用 ssh-agent 做认证（硬件密钥、agent forwarding 场景）
+    pub ssh_use_agent: Option<bool>,
+    pub ssl: Option<SslConfig>,
+    // 连接池大小，MySql/Postgres 的 sqlx pool 和 SqlServer 的 bb8 pool 共用同一个旋钮，
+    // 不填时沿用各自原本的默认值；SQLite 的连接池固定为 5 条连接，不读这个字段
+    pub max_pool_size: Option<u32>,
+    // 敏感字段（password/ssh_password/ssh_key）是否已迁移到系统密钥链；
+    // 旧版本明文保存的连接在首次加载时会被迁移并置为 Some(true)
+    pub secrets_in_keychain: Option<bool>,
+    pub sqlite: Option<SqliteOptions>,
+}
+
+// SQLite 专属的打开方式：是否允许创建新文件，或者强制只读（避免误写生产数据文件）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SqliteOpenMode {
+    ReadWriteCreate,
+    ReadWrite,
+    ReadOnly,
+}
+
+impl Default for SqliteOpenMode {
+    fn default() -> Self {
+        SqliteOpenMode::ReadWriteCreate
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SqliteSynchronousMode {
+    Off,
+    Normal,
+    Full,
+    Extra,
+}
+
+impl Default for SqliteSynchronousMode {
+    fn default() -> Self {
+        SqliteSynchronousMode::Normal
+    }
+}
+
+// 对应连接时要下发的几个常用 PRAGMA；不填时分别回退到外键约束开启、WAL 模式开启、
+// 5 秒忙等超时、NORMAL 同步级别，这是多数场景下比 sqlx 默认值更合适的设置。忙等超时
+// 主要是为了 db_update_row/db_delete_row 跟一个并发打开的只读连接抢锁时不要立刻报
+// "database is locked"；外键约束打开则是为了让级联/限制行为跟表结构查看器里看到的一致
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SqliteOptions {
+    #[serde(default)]
+    pub open_mode: SqliteOpenMode,
+    pub foreign_keys: Option<bool>,
+    pub journal_mode_wal: Option<bool>,
+    pub busy_timeout_ms: Option<u32>,
+    pub synchronous: Option<SqliteSynchronousMode>,
+}
+
+// TLS 加密级别，对应 sqlx 的 ssl-mode / libpq sslmode 语义。底层 TLS 后端由 sqlx/tiberius
+// 的 cargo feature 在编译期固定（目前是各自默认的 native-tls），这里只做运行时的模式/证书选择，
+// 还没有暴露 rustls 之类的编译期切换
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SslMode {
+    Disable,
+    Prefer,
+    Require,
+    VerifyCa,
+    VerifyFull,
+}
+
+impl Default for SslMode {
+    fn default() -> Self {
+        SslMode::Prefer
+    }
+}
+
+// CA/客户端证书既可以是本地文件路径，也可以内嵌为 base64，方便随 connections.json 一起保存
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SslConfig {
+    #[serde(default)]
+    pub mode: SslMode,
+    pub ca_cert: Option<String>,
+    pub ca_cert_base64: Option<String>,
+    pub client_cert: Option<String>,
+    pub client_cert_base64: Option<String>,
+    pub client_key: Option<String>,
+    pub client_key_base64: Option<String>,
+}
+
+impl SslConfig {
+    // 将 base64 内嵌的证书/私钥落盘到临时文件，返回可供驱动读取的路径
+    fn materialize(source: &Option<String>, source_base64: &Option<String>, suffix: &str) -> Result<Option<PathBuf>, DbError> {
+        if let Some(path) = source {
+            return Ok(Some(PathBuf::from(path)));
+        }
+        if let Some(b64) = source_base64 {
+            use base64::Engine;
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(b64)
+                .map_err(|e| DbError::ConnectionError(format!("证书 base64 解码失败: {}", e), None))?;
+            let mut path = std::env::temp_dir();
+            path.push(format!("easysql-{}-{}{}", std::process::id(), uuid_like_suffix(), suffix));
+            std::fs::write(&path, bytes)
+                .map_err(|e| DbError::ConnectionError(format!("写入临时证书失败: {}", e), None))?;
+            return Ok(Some(path));
+        }
+        Ok(None)
+    }
+
+    pub fn ca_path(&self) -> Result<Option<PathBuf>, DbError> {
+        Self::materialize(&self.ca_cert, &self.ca_cert_base64, ".ca.pem")
+    }
+
+    pub fn client_cert_path(&self) -> Result<Option<PathBuf>, DbError> {
+        Self::materialize(&self.client_cert, &self.client_cert_base64, ".client.pem")
+    }
+
+    pub fn client_key_path(&self) -> Result<Option<PathBuf>, DbError> {
+        Self::materialize(&self.client_key, &self.client_key_base64, ".client.key")
+    }
+}
+
+// 临时证书文件名需要唯一但不依赖系统时钟/随机数即可，用进程内自增计数（配合文件名里的进程号）做后缀
+fn uuid_like_suffix() -> u64 {
+    static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,6 +463,131 @@ pub struct QueryResult {
     pub error: Option<String>,
     #[serde(rename = "affectedRows")]
     pub affected_rows: Option<i64>,
+    #[serde(rename = "errorDetail")]
+    pub error_detail: Option<StructuredError>,
+}
+
+// 跨驱动统一的错误分类：能拿到具体违例类型（唯一/外键/非空/检查约束）的就细分，
+// 拿不到的（比如 MySQL 的 SQLSTATE 23000 本身不区分子类型）退回 SQLSTATE class 粗分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    UniqueViolation,
+    ForeignKeyViolation,
+    NotNullViolation,
+    CheckViolation,
+    SyntaxError,
+    PermissionDenied,
+    ConnectionFailure,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorSeverity {
+    Warning,
+    Error,
+    Fatal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredError {
+    pub message: String,
+    pub code: Option<String>,
+    pub category: ErrorCategory,
+    pub severity: ErrorSeverity,
+}
+
+// 按 SQLSTATE 的 class（前两位，部分已知完整码用全码）归类，未知的一律落入 Other；
+// 仅在驱动的 DatabaseError::kind() 拿不到细分违例类型时才会被用到（见 classify_sqlx_error）
+fn classify_sqlstate(code: &str) -> ErrorCategory {
+    match code {
+        "23505" => ErrorCategory::UniqueViolation,
+        "23503" => ErrorCategory::ForeignKeyViolation,
+        "23502" => ErrorCategory::NotNullViolation,
+        "23514" => ErrorCategory::CheckViolation,
+        _ => match &code[..code.len().min(2)] {
+            "23" => ErrorCategory::UniqueViolation,
+            "42" => ErrorCategory::SyntaxError,
+            "28" => ErrorCategory::PermissionDenied,
+            "08" => ErrorCategory::ConnectionFailure,
+            _ => ErrorCategory::Other,
+        },
+    }
+}
+
+pub fn classify_sqlx_error(e: &sqlx::Error) -> StructuredError {
+    match e {
+        sqlx::Error::Database(db_err) => {
+            let code = db_err.code().map(|c| c.to_string());
+            // MySQL 的 SQLSTATE（23000）本身不区分唯一/外键/非空，优先用驱动已经解析好的
+            // ErrorKind；kind() 返回 Other 时再退回按 SQLSTATE 粗分类
+            let category = match db_err.kind() {
+                sqlx::error::ErrorKind::UniqueViolation => ErrorCategory::UniqueViolation,
+                sqlx::error::ErrorKind::ForeignKeyViolation => ErrorCategory::ForeignKeyViolation,
+                sqlx::error::ErrorKind::NotNullViolation => ErrorCategory::NotNullViolation,
+                sqlx::error::ErrorKind::CheckViolation => ErrorCategory::CheckViolation,
+                _ => code.as_deref().map(classify_sqlstate).unwrap_or(ErrorCategory::Other),
+            };
+            StructuredError {
+                message: db_err.message().to_string(),
+                code,
+                category,
+                severity: ErrorSeverity::Error,
+            }
+        }
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed => StructuredError {
+            message: e.to_string(),
+            code: None,
+            category: ErrorCategory::ConnectionFailure,
+            severity: ErrorSeverity::Fatal,
+        },
+        other => StructuredError {
+            message: other.to_string(),
+            code: None,
+            category: ErrorCategory::Other,
+            severity: ErrorSeverity::Error,
+        },
+    }
+}
+
+// Tiberius 没有像 sqlx 那样统一的 SQLSTATE，只能按 SQL Server 原生错误号分类，
+// 参照几个最常见的号段（2627/2601 唯一约束，547 外键/检查约束，515 非空，
+// 102/207/208 语法与对象解析，229/230 权限，4060/18456 连接与鉴权）
+pub fn classify_tiberius_error(e: &tiberius::error::Error) -> StructuredError {
+    let message = e.to_string();
+    if let tiberius::error::Error::Server(token) = e {
+        let code = token.code();
+        let category = match code {
+            2627 | 2601 => ErrorCategory::UniqueViolation,
+            547 => ErrorCategory::ForeignKeyViolation,
+            515 => ErrorCategory::NotNullViolation,
+            102 | 207 | 208 => ErrorCategory::SyntaxError,
+            229 | 230 => ErrorCategory::PermissionDenied,
+            4060 | 18456 => ErrorCategory::ConnectionFailure,
+            _ => ErrorCategory::Other,
+        };
+        return StructuredError {
+            message: token.message().to_string(),
+            code: Some(code.to_string()),
+            category,
+            severity: ErrorSeverity::Error,
+        };
+    }
+
+    StructuredError {
+        message,
+        code: None,
+        category: ErrorCategory::ConnectionFailure,
+        severity: ErrorSeverity::Fatal,
+    }
+}
+
+// SQLite 的约束违例细分现在已经由 sqlx 的 DatabaseError::kind() 覆盖，这里只是
+// 保留一个同名入口，方便调用方不用关心具体是哪个后端
+pub fn classify_sqlite_error(e: &sqlx::Error) -> StructuredError {
+    classify_sqlx_error(e)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,6 +600,25 @@ pub struct TableDataResult {
     pub page_size: i32,
 }
 
+// 游标（keyset）分页结果：深翻页场景下用 "WHERE order_col > :last" 代替 OFFSET，
+// 避免引擎为了跳过前面的行而做整表扫描；first/last_value 供前端请求下一页/上一页时回传
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeysetPageResult {
+    pub columns: Vec<ColumnInfo>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub total: i64,
+    #[serde(rename = "pageSize")]
+    pub page_size: i32,
+    #[serde(rename = "orderColumn")]
+    pub order_column: String,
+    #[serde(rename = "firstValue")]
+    pub first_value: Option<serde_json::Value>,
+    #[serde(rename = "lastValue")]
+    pub last_value: Option<serde_json::Value>,
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+}
+
 // 数据库连接枚举
 pub enum DbConnection {
     MySql(sqlx::MySqlPool),
@@ -85,7 +628,47 @@ pub enum DbConnection {
 }
 
 pub struct SqlServerConnection {
-    pub config: tiberius::Config,
+    pub pool: bb8::Pool<SqlServerConnectionManager>,
+}
+
+// bb8 连接管理器：负责按需新建 tiberius 客户端，以及在从池中取出时用 SELECT 1 校验连接是否仍然存活，
+// 使 SQL Server 分支获得和 sqlx 连接池一样的复用行为
+#[derive(Clone)]
+pub struct SqlServerConnectionManager {
+    config: tiberius::Config,
+}
+
+pub type SqlServerClient = tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>;
+
+#[async_trait::async_trait]
+impl bb8::ManageConnection for SqlServerConnectionManager {
+    type Connection = SqlServerClient;
+    type Error = DbError;
+
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        use tokio::net::TcpStream;
+        use tokio_util::compat::TokioAsyncWriteCompatExt;
+
+        let tcp = TcpStream::connect(self.config.get_addr())
+            .await
+            .map_err(|e| DbError::ConnectionError(e.to_string(), None))?;
+        tcp.set_nodelay(true).ok();
+
+        tiberius::Client::connect(self.config.clone(), tcp.compat_write())
+            .await
+            .map_err(|e| DbError::ConnectionError(e.to_string(), Some(classify_tiberius_error(&e))))
+    }
+
+    async fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        conn.simple_query("SELECT 1")
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+        Ok(())
+    }
+
+    fn has_broken(&self, _conn: &mut Self::Connection) -> bool {
+        false
+    }
 }
 
 // 连接信息存储
@@ -104,36 +687,36 @@ pub fn init() {
 }
 
 impl DbConnection {
-    pub async fn test_mysql(host: &str, port: u16, user: &str, password: &str, database: Option<&str>) -> Result<(), DbError> {
+    pub async fn test_mysql(host: &str, port: u16, user: &str, password: &str, database: Option<&str>, ssl: Option<&SslConfig>) -> Result<(), DbError> {
         let db = database.unwrap_or("mysql");
-        let url = format!("mysql://{}:{}@{}:{}/{}", user, password, host, port, db);
-        
+        let options = mysql_connect_options(host, port, user, password, db, ssl)?;
+
         let pool = sqlx::mysql::MySqlPoolOptions::new()
             .max_connections(1)
             .acquire_timeout(std::time::Duration::from_secs(10))
-            .connect(&url)
+            .connect_with(options)
             .await
-            .map_err(|e| DbError::ConnectionError(e.to_string()))?;
-        
+            .map_err(|e| DbError::ConnectionError(e.to_string(), Some(classify_sqlx_error(&e))))?;
+
         sqlx::query("SELECT 1")
             .execute(&pool)
             .await
             .map_err(|e| DbError::QueryError(e.to_string()))?;
-        
+
         pool.close().await;
         Ok(())
     }
 
-    pub async fn test_postgres(host: &str, port: u16, user: &str, password: &str, database: Option<&str>) -> Result<(), DbError> {
+    pub async fn test_postgres(host: &str, port: u16, user: &str, password: &str, database: Option<&str>, ssl: Option<&SslConfig>) -> Result<(), DbError> {
         let db = database.unwrap_or("postgres");
-        let url = format!("postgres://{}:{}@{}:{}/{}", user, password, host, port, db);
-        
+        let options = postgres_connect_options(host, port, user, password, db, ssl)?;
+
         let pool = sqlx::postgres::PgPoolOptions::new()
             .max_connections(1)
             .acquire_timeout(std::time::Duration::from_secs(10))
-            .connect(&url)
+            .connect_with(options)
             .await
-            .map_err(|e| DbError::ConnectionError(e.to_string()))?;
+            .map_err(|e| DbError::ConnectionError(e.to_string(), Some(classify_sqlx_error(&e))))?;
         
         sqlx::query("SELECT 1")
             .execute(&pool)
@@ -144,15 +727,15 @@ impl DbConnection {
         Ok(())
     }
 
-    pub async fn test_sqlite(path: &str) -> Result<(), DbError> {
-        let url = format!("sqlite:{}?mode=rwc", path);
-        
+    pub async fn test_sqlite(path: &str, options: Option<&SqliteOptions>) -> Result<(), DbError> {
+        let connect_options = sqlite_connect_options(path, options);
+
         let pool = sqlx::sqlite::SqlitePoolOptions::new()
             .max_connections(1)
-            .connect(&url)
+            .connect_with(connect_options)
             .await
-            .map_err(|e| DbError::ConnectionError(e.to_string()))?;
-        
+            .map_err(|e| DbError::ConnectionError(e.to_string(), Some(classify_sqlite_error(&e))))?;
+
         sqlx::query("SELECT 1")
             .execute(&pool)
             .await
@@ -162,29 +745,22 @@ impl DbConnection {
         Ok(())
     }
 
-    pub async fn test_sqlserver(host: &str, port: u16, user: &str, password: &str, database: Option<&str>) -> Result<(), DbError> {
-        use tiberius::{Client, Config, AuthMethod};
+    pub async fn test_sqlserver(host: &str, port: u16, user: &str, password: &str, database: Option<&str>, ssl: Option<&SslConfig>) -> Result<(), DbError> {
+        use tiberius::Client;
         use tokio::net::TcpStream;
         use tokio_util::compat::TokioAsyncWriteCompatExt;
 
-        let mut config = Config::new();
-        config.host(host);
-        config.port(port);
-        config.authentication(AuthMethod::sql_server(user, password));
-        if let Some(db) = database {
-            config.database(db);
-        }
-        config.trust_cert();
+        let config = sqlserver_config(host, port, user, password, database, ssl)?;
 
         let tcp = TcpStream::connect(config.get_addr())
             .await
-            .map_err(|e| DbError::ConnectionError(e.to_string()))?;
+            .map_err(|e| DbError::ConnectionError(e.to_string(), None))?;
 
         tcp.set_nodelay(true).ok();
 
         let mut client = Client::connect(config, tcp.compat_write())
             .await
-            .map_err(|e| DbError::ConnectionError(e.to_string()))?;
+            .map_err(|e| DbError::ConnectionError(e.to_string(), Some(classify_tiberius_error(&e))))?;
 
         client.simple_query("SELECT 1")
             .await
@@ -193,61 +769,64 @@ impl DbConnection {
         Ok(())
     }
 
-    pub async fn connect_mysql(host: &str, port: u16, user: &str, password: &str, database: Option<&str>) -> Result<Self, DbError> {
+    pub async fn connect_mysql(host: &str, port: u16, user: &str, password: &str, database: Option<&str>, ssl: Option<&SslConfig>, max_pool_size: Option<u32>) -> Result<Self, DbError> {
         let db = database.unwrap_or("mysql");
-        let url = format!("mysql://{}:{}@{}:{}/{}", user, password, host, port, db);
-        
+        let options = mysql_connect_options(host, port, user, password, db, ssl)?;
+
         let pool = sqlx::mysql::MySqlPoolOptions::new()
-            .max_connections(10)
+            .max_connections(max_pool_size.unwrap_or(10))
             .min_connections(1)
             .acquire_timeout(std::time::Duration::from_secs(30))
             .idle_timeout(std::time::Duration::from_secs(600))
-            .connect(&url)
+            .connect_with(options)
             .await
-            .map_err(|e| DbError::ConnectionError(e.to_string()))?;
-        
+            .map_err(|e| DbError::ConnectionError(e.to_string(), Some(classify_sqlx_error(&e))))?;
+
         Ok(DbConnection::MySql(pool))
     }
 
-    pub async fn connect_postgres(host: &str, port: u16, user: &str, password: &str, database: Option<&str>) -> Result<Self, DbError> {
+    pub async fn connect_postgres(host: &str, port: u16, user: &str, password: &str, database: Option<&str>, ssl: Option<&SslConfig>, max_pool_size: Option<u32>) -> Result<Self, DbError> {
         let db = database.unwrap_or("postgres");
-        let url = format!("postgres://{}:{}@{}:{}/{}", user, password, host, port, db);
-        
+        let options = postgres_connect_options(host, port, user, password, db, ssl)?;
+
         let pool = sqlx::postgres::PgPoolOptions::new()
-            .max_connections(10)
+            .max_connections(max_pool_size.unwrap_or(10))
             .min_connections(1)
             .acquire_timeout(std::time::Duration::from_secs(30))
             .idle_timeout(std::time::Duration::from_secs(600))
-            .connect(&url)
+            .connect_with(options)
             .await
-            .map_err(|e| DbError::ConnectionError(e.to_string()))?;
-        
+            .map_err(|e| DbError::ConnectionError(e.to_string(), Some(classify_sqlx_error(&e))))?;
+
         Ok(DbConnection::Postgres(pool))
     }
 
-    pub async fn connect_sqlite(path: &str) -> Result<Self, DbError> {
-        let url = format!("sqlite:{}?mode=rwc", path);
-        
+    pub async fn connect_sqlite(path: &str, options: Option<&SqliteOptions>) -> Result<Self, DbError> {
+        let connect_options = sqlite_connect_options(path, options);
+
         let pool = sqlx::sqlite::SqlitePoolOptions::new()
             .max_connections(5)
-            .connect(&url)
+            .connect_with(connect_options)
             .await
-            .map_err(|e| DbError::ConnectionError(e.to_string()))?;
-        
+            .map_err(|e| DbError::ConnectionError(e.to_string(), Some(classify_sqlite_error(&e))))?;
+
         Ok(DbConnection::Sqlite(pool))
     }
 
-    pub async fn connect_sqlserver(host: &str, port: u16, user: &str, password: &str, database: Option<&str>) -> Result<Self, DbError> {
-        let mut config = tiberius::Config::new();
-        config.host(host);
-        config.port(port);
-        config.authentication(tiberius::AuthMethod::sql_server(user, password));
-        if let Some(db) = database {
-            config.database(db);
-        }
-        config.trust_cert();
+    pub async fn connect_sqlserver(host: &str, port: u16, user: &str, password: &str, database: Option<&str>, ssl: Option<&SslConfig>, max_pool_size: Option<u32>) -> Result<Self, DbError> {
+        let config = sqlserver_config(host, port, user, password, database, ssl)?;
+        let manager = SqlServerConnectionManager { config };
 
-        Ok(DbConnection::SqlServer(SqlServerConnection { config }))
+        let pool = bb8::Pool::builder()
+            .max_size(max_pool_size.unwrap_or(10))
+            .min_idle(Some(1))
+            .connection_timeout(std::time::Duration::from_secs(30))
+            .idle_timeout(Some(std::time::Duration::from_secs(600)))
+            .build(manager)
+            .await
+            .map_err(|e| DbError::ConnectionError(e.to_string(), e.structured_detail()))?;
+
+        Ok(DbConnection::SqlServer(SqlServerConnection { pool }))
     }
 }
 
@@ -260,3 +839,162 @@ pub fn resolve_host(host: &str) -> String {
     }
 }
 
+fn mysql_connect_options(
+    host: &str,
+    port: u16,
+    user: &str,
+    password: &str,
+    database: &str,
+    ssl: Option<&SslConfig>,
+) -> Result<sqlx::mysql::MySqlConnectOptions, DbError> {
+    let mut options = sqlx::mysql::MySqlConnectOptions::new()
+        .host(host)
+        .port(port)
+        .username(user)
+        .password(password)
+        .database(database);
+
+    if let Some(ssl) = ssl {
+        options = options.ssl_mode(match ssl.mode {
+            SslMode::Disable => sqlx::mysql::MySqlSslMode::Disabled,
+            SslMode::Prefer => sqlx::mysql::MySqlSslMode::Preferred,
+            SslMode::Require => sqlx::mysql::MySqlSslMode::Required,
+            SslMode::VerifyCa => sqlx::mysql::MySqlSslMode::VerifyCa,
+            SslMode::VerifyFull => sqlx::mysql::MySqlSslMode::VerifyIdentity,
+        });
+        if let Some(ca) = ssl.ca_path()? {
+            options = options.ssl_ca(ca);
+        }
+        if let Some(cert) = ssl.client_cert_path()? {
+            options = options.ssl_client_cert(cert);
+        }
+        if let Some(key) = ssl.client_key_path()? {
+            options = options.ssl_client_key(key);
+        }
+    }
+
+    Ok(options)
+}
+
+fn postgres_connect_options(
+    host: &str,
+    port: u16,
+    user: &str,
+    password: &str,
+    database: &str,
+    ssl: Option<&SslConfig>,
+) -> Result<sqlx::postgres::PgConnectOptions, DbError> {
+    let mut options = sqlx::postgres::PgConnectOptions::new()
+        .host(host)
+        .port(port)
+        .username(user)
+        .password(password)
+        .database(database);
+
+    if let Some(ssl) = ssl {
+        options = options.ssl_mode(match ssl.mode {
+            SslMode::Disable => sqlx::postgres::PgSslMode::Disable,
+            SslMode::Prefer => sqlx::postgres::PgSslMode::Prefer,
+            SslMode::Require => sqlx::postgres::PgSslMode::Require,
+            SslMode::VerifyCa => sqlx::postgres::PgSslMode::VerifyCa,
+            SslMode::VerifyFull => sqlx::postgres::PgSslMode::VerifyFull,
+        });
+        if let Some(ca) = ssl.ca_path()? {
+            options = options.ssl_root_cert(ca);
+        }
+        if let Some(cert) = ssl.client_cert_path()? {
+            options = options.ssl_client_cert(cert);
+        }
+        if let Some(key) = ssl.client_key_path()? {
+            options = options.ssl_client_key(key);
+        }
+    }
+
+    Ok(options)
+}
+
+// 按 SqliteOptions 下发常用 PRAGMA：外键约束、WAL 模式、忙等超时、同步级别，
+// 以及 open_mode 对应的只读/禁止创建开关；不传 options 时回退到相对安全的默认值
+fn sqlite_connect_options(path: &str, options: Option<&SqliteOptions>) -> sqlx::sqlite::SqliteConnectOptions {
+    use sqlx::sqlite::{SqliteJournalMode, SqliteSynchronous};
+
+    let default = SqliteOptions::default();
+    let options = options.unwrap_or(&default);
+
+    let mut opts = sqlx::sqlite::SqliteConnectOptions::new().filename(path);
+
+    opts = match options.open_mode {
+        SqliteOpenMode::ReadOnly => opts.read_only(true),
+        SqliteOpenMode::ReadWrite => opts.create_if_missing(false),
+        SqliteOpenMode::ReadWriteCreate => opts.create_if_missing(true),
+    };
+
+    opts = opts.foreign_keys(options.foreign_keys.unwrap_or(true));
+    opts = opts.journal_mode(if options.journal_mode_wal.unwrap_or(true) {
+        SqliteJournalMode::Wal
+    } else {
+        SqliteJournalMode::Delete
+    });
+    opts = opts.busy_timeout(std::time::Duration::from_millis(
+        options.busy_timeout_ms.unwrap_or(5000) as u64,
+    ));
+    opts = opts.synchronous(match options.synchronous.unwrap_or_default() {
+        SqliteSynchronousMode::Off => SqliteSynchronous::Off,
+        SqliteSynchronousMode::Normal => SqliteSynchronous::Normal,
+        SqliteSynchronousMode::Full => SqliteSynchronous::Full,
+        SqliteSynchronousMode::Extra => SqliteSynchronous::Extra,
+    });
+
+    opts
+}
+
+fn sqlserver_config(
+    host: &str,
+    port: u16,
+    user: &str,
+    password: &str,
+    database: Option<&str>,
+    ssl: Option<&SslConfig>,
+) -> Result<tiberius::Config, DbError> {
+    let mut config = tiberius::Config::new();
+    config.host(host);
+    config.port(port);
+    config.authentication(tiberius::AuthMethod::sql_server(user, password));
+    if let Some(db) = database {
+        config.database(db);
+    }
+
+    match ssl {
+        None | Some(SslConfig { mode: SslMode::Disable, .. }) => {
+            config.encryption(tiberius::EncryptionLevel::NotSupported);
+        }
+        Some(ssl) if ssl.mode == SslMode::Prefer || ssl.mode == SslMode::Require => {
+            config.encryption(tiberius::EncryptionLevel::Required);
+            config.trust_cert();
+        }
+        Some(ssl) => {
+            config.encryption(tiberius::EncryptionLevel::Required);
+            // tiberius/TDS 没有像 MySQL、Postgres 驱动那样的客户端证书（双向 TLS）配置项，
+            // 只能校验服务端证书；为避免像之前那样悄悄丢弃用户配的客户端证书，这里显式报错
+            // 而不是假装校验过了
+            if ssl.client_cert_path()?.is_some() || ssl.client_key_path()?.is_some() {
+                return Err(DbError::ConnectionError(
+                    "SQL Server 连接暂不支持客户端证书（双向 TLS），请改用仅校验 CA 证书的模式".to_string(),
+                    None,
+                ));
+            }
+            if let Some(ca) = ssl.ca_path()? {
+                config.trust_cert_ca(ca.to_string_lossy().into_owned());
+            } else {
+                return Err(DbError::ConnectionError(
+                    "SQL Server 连接要求校验服务端证书（VerifyCa/VerifyFull），但未配置 CA 证书路径，拒绝静默信任任意证书"
+                        .to_string(),
+                    None,
+                ));
+            }
+        }
+    }
+
+    Ok(config)
+}
+
@@ -3,8 +3,12 @@
 
 mod database;
 mod commands;
+mod cdc;
 mod config;
+mod query_stream;
+mod secrets;
 mod ssh;
+mod subscription;
 
 use tauri::Manager;
 use tracing_subscriber;
@@ -27,7 +31,9 @@ fn main() {
         .setup(|app| {
             // 初始化数据库连接管理器
             database::init();
-            
+            // 订阅模块要在后台任务里主动推事件，这里把 AppHandle 存成静态供其使用
+            subscription::init(app.handle().clone());
+
             // 获取主窗口并设置
             if let Some(window) = app.get_webview_window("main") {
                 // Windows 上启用窗口阴影效果
@@ -50,14 +56,25 @@ fn main() {
             commands::db_connect,
             commands::db_disconnect,
             commands::db_query,
+            commands::db_query_params,
+            commands::db_execute_script,
+            query_stream::db_query_page,
+            query_stream::db_query_stream_start,
+            query_stream::db_query_stream_stop,
             commands::db_get_databases,
             commands::db_get_tables,
             commands::db_get_columns,
             commands::db_get_table_data,
+            commands::db_get_table_data_keyset,
             commands::db_update_row,
             commands::db_delete_row,
             commands::db_backup,
             commands::db_export_table,
+            // 变更流
+            cdc::db_stream_changes,
+            cdc::db_stop_stream,
+            subscription::db_subscribe,
+            subscription::db_unsubscribe,
             // 配置操作
             commands::config_save,
             commands::config_load,
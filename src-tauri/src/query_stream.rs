@@ -0,0 +1,284 @@
+// 大结果集的分页/流式拉取：db_query 一次性 fetch_all，在百万行级别的表上会把整个
+// 结果集读进内存甚至卡死界面。这里先提供一个按偏移量分页的 db_query_page，再在此之上
+// 包一层按 token 管理的后台任务，一页接一页地拉取并通过 Tauri 事件推给前端，页面翻走或
+// 主动关闭 token 时释放游标，避免无界内存增长。
+use crate::commands;
+use crate::database::{DbConnection, QueryResult, CONNECTIONS};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use sqlx::Executor;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryChunk {
+    pub token: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub done: bool,
+    pub error: Option<String>,
+}
+
+struct StreamCursor {
+    stop: Arc<AtomicBool>,
+}
+
+// 每个活跃的流式查询对应一条记录，key 为 db_query_stream_start 生成的 token
+static QUERY_STREAMS: Lazy<RwLock<HashMap<String, StreamCursor>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+static TOKEN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// 给定一条任意 SQL，按后端方言包一层分页子句，用于只拉一页而不必读入整个结果集。
+// 任意 SQL 没有现成的主键可用，但 order_clause 按列序号（ORDER BY 1, 2, ...）排序后，
+// 只要输出的列内容不变，排序结果就是确定的，不会再依赖引擎未排序时的扫描顺序——
+// 这也是 SQL Server 的 OFFSET...FETCH 强制要求 ORDER BY 的根本原因；只有一列可排序的
+// 列都没有时，order_by_ordinal 才退回 ORDER BY (SELECT NULL) 占位
+fn paginate_sql(db_type: &str, sql: &str, order_clause: &str, offset: i64, limit: i64) -> String {
+    let inner = sql.trim().trim_end_matches(';');
+    match db_type {
+        "sqlserver" => format!(
+            "SELECT * FROM ({}) AS page_source {} OFFSET {} ROWS FETCH NEXT {} ROWS ONLY",
+            inner, order_clause, offset, limit
+        ),
+        _ => format!(
+            "SELECT * FROM ({}) AS page_source {} LIMIT {} OFFSET {}",
+            inner, order_clause, limit, offset
+        ),
+    }
+}
+
+fn connection_db_type(id: &str) -> Option<String> {
+    CONNECTIONS.read().get(id).map(|info| info.config.db_type.clone())
+}
+
+// 探测 sql 的输出列类型（不依赖实际拿到的行数）：MySQL/Postgres/SQLite 走 sqlx 的
+// describe，只 prepare 不执行；SQL Server 没有等价的 sqlx API，改用
+// sys.dm_exec_describe_first_result_set，同样不会真的跑一遍查询。之前用
+// `WHERE 1=0` 探测列数时，四个方言的 db_query 都是"拿第一行的 columns() 才知道列名/
+// 类型"，0 行时直接早退返回空列，等于探测必然失败——describe 不依赖行数据，顺带修了这个。
+async fn probe_column_types(id: &str, sql: &str) -> Option<Vec<(String, String)>> {
+    let inner = sql.trim().trim_end_matches(';');
+    let conn_info = {
+        let connections = CONNECTIONS.read();
+        connections.get(id)?.clone()
+    };
+
+    match &conn_info.connection {
+        DbConnection::MySql(pool) => {
+            let probe_sql = format!("SELECT * FROM ({}) AS probe_source", inner);
+            let described = pool.describe(&probe_sql).await.ok()?;
+            Some(
+                described
+                    .columns()
+                    .iter()
+                    .map(|c| (c.name().to_string(), c.type_info().name().to_string()))
+                    .collect(),
+            )
+        }
+        DbConnection::Postgres(pool) => {
+            let probe_sql = format!("SELECT * FROM ({}) AS probe_source", inner);
+            let described = pool.describe(&probe_sql).await.ok()?;
+            Some(
+                described
+                    .columns()
+                    .iter()
+                    .map(|c| (c.name().to_string(), c.type_info().name().to_string()))
+                    .collect(),
+            )
+        }
+        DbConnection::Sqlite(pool) => {
+            let probe_sql = format!("SELECT * FROM ({}) AS probe_source", inner);
+            let described = pool.describe(&probe_sql).await.ok()?;
+            Some(
+                described
+                    .columns()
+                    .iter()
+                    .map(|c| (c.name().to_string(), c.type_info().name().to_string()))
+                    .collect(),
+            )
+        }
+        DbConnection::SqlServer(conn) => probe_column_types_sqlserver(conn, inner).await,
+    }
+}
+
+async fn probe_column_types_sqlserver(
+    conn: &crate::database::SqlServerConnection,
+    inner_sql: &str,
+) -> Option<Vec<(String, String)>> {
+    let mut client = conn.pool.get().await.ok()?;
+    // sys.dm_exec_describe_first_result_set 只分析语句会返回什么结果集，不会真的执行它，
+    // system_type_name 就是我们要的、用来判断该列是否支持默认比较/排序操作符的类型名
+    let escaped = inner_sql.replace('\'', "''");
+    let describe_sql = format!(
+        "SELECT name, system_type_name FROM sys.dm_exec_describe_first_result_set(N'{}', NULL, 0) ORDER BY column_ordinal",
+        escaped
+    );
+    let result = client.simple_query(describe_sql).await.ok()?;
+    let mut columns = vec![];
+    for result_set in result.into_results().await.ok()? {
+        for row in result_set {
+            let name: String = row.try_get::<&str, _>(0).ok().flatten().unwrap_or("").to_string();
+            let type_name: String = row.try_get::<&str, _>(1).ok().flatten().unwrap_or("").to_string();
+            columns.push((name, type_name));
+        }
+    }
+    if columns.is_empty() {
+        None
+    } else {
+        Some(columns)
+    }
+}
+
+// Postgres json/jsonb 以及几何类型没有默认的 btree 比较操作符，SQL Server 的
+// text/ntext/image/xml/geometry/geography/hierarchyid 同理——这些列一旦出现在
+// ORDER BY 里，数据库会直接报"无法识别排序操作符"之类的错误，必须从 ORDER BY 里剔除
+fn is_orderable_type(db_type: &str, type_name: &str) -> bool {
+    let t = type_name.to_lowercase();
+    match db_type {
+        "postgres" => !matches!(
+            t.as_str(),
+            "json" | "jsonb" | "point" | "line" | "lseg" | "box" | "path" | "polygon" | "circle"
+        ) && !t.ends_with("[]"),
+        "sqlserver" => !matches!(
+            t.as_str(),
+            "text" | "ntext" | "image" | "xml" | "geometry" | "geography" | "hierarchyid"
+        ),
+        _ => true,
+    }
+}
+
+// 按探测到的列类型拼 ORDER BY，只挑能排序的列序号；一列都挑不出来时退化为空子句
+// （SQL Server 例外，返回占位的 ORDER BY (SELECT NULL)，因为 OFFSET...FETCH 语法本身强制
+// 要求有 ORDER BY），结果是分页仍然能跑，只是退化成无序（和分页前的行为一致）
+fn order_by_ordinal(column_types: Option<Vec<(String, String)>>, db_type: &str) -> String {
+    let orderable: Vec<String> = match &column_types {
+        Some(cols) => cols
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, type_name))| is_orderable_type(db_type, type_name))
+            .map(|(i, _)| (i + 1).to_string())
+            .collect(),
+        None => vec![],
+    };
+
+    if !orderable.is_empty() {
+        format!("ORDER BY {}", orderable.join(", "))
+    } else if db_type == "sqlserver" {
+        "ORDER BY (SELECT NULL)".to_string()
+    } else {
+        String::new()
+    }
+}
+
+#[tauri::command]
+pub async fn db_query_page(id: String, sql: String, offset: i64, limit: i64) -> QueryResult {
+    let db_type = match connection_db_type(&id) {
+        Some(t) => t,
+        None => {
+            return QueryResult {
+                columns: vec![],
+                rows: vec![],
+                error: Some("未连接".to_string()),
+                affected_rows: None,
+                error_detail: None,
+            }
+        }
+    };
+
+    let column_types = probe_column_types(&id, &sql).await;
+    let order_clause = order_by_ordinal(column_types, &db_type);
+    let paged_sql = paginate_sql(&db_type, &sql, &order_clause, offset, limit);
+    commands::db_query(id, paged_sql).await
+}
+
+// 开启一路分页流：后台任务按 chunk_size 反复调用 db_query_page 并把每一页通过
+// "query-stream-chunk" 事件推给前端，直到页面不满（说明已到结果集末尾）、出错，
+// 或前端调用 db_query_stream_stop 主动释放游标
+#[tauri::command]
+pub async fn db_query_stream_start(
+    app: AppHandle,
+    id: String,
+    sql: String,
+    chunk_size: Option<i64>,
+) -> Result<String, String> {
+    if connection_db_type(&id).is_none() {
+        return Err("未连接".to_string());
+    }
+
+    let chunk_size = chunk_size.unwrap_or(500).max(1);
+    let token = format!("{}-{}", id, TOKEN_COUNTER.fetch_add(1, Ordering::Relaxed));
+    let stop = Arc::new(AtomicBool::new(false));
+
+    QUERY_STREAMS.write().insert(token.clone(), StreamCursor { stop: stop.clone() });
+
+    tokio::spawn(stream_pages(app, id, sql, token.clone(), chunk_size, stop));
+
+    Ok(token)
+}
+
+#[tauri::command]
+pub fn db_query_stream_stop(token: String) -> bool {
+    if let Some(cursor) = QUERY_STREAMS.write().remove(&token) {
+        cursor.stop.store(true, Ordering::SeqCst);
+        true
+    } else {
+        false
+    }
+}
+
+async fn stream_pages(
+    app: AppHandle,
+    id: String,
+    sql: String,
+    token: String,
+    chunk_size: i64,
+    stop: Arc<AtomicBool>,
+) {
+    let mut offset = 0i64;
+    loop {
+        if stop.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let page = db_query_page(id.clone(), sql.clone(), offset, chunk_size).await;
+
+        if let Some(error) = page.error {
+            let _ = app.emit(
+                "query-stream-chunk",
+                QueryChunk {
+                    token: token.clone(),
+                    columns: vec![],
+                    rows: vec![],
+                    done: true,
+                    error: Some(error),
+                },
+            );
+            QUERY_STREAMS.write().remove(&token);
+            return;
+        }
+
+        let row_count = page.rows.len() as i64;
+        let done = row_count < chunk_size;
+
+        let _ = app.emit(
+            "query-stream-chunk",
+            QueryChunk {
+                token: token.clone(),
+                columns: page.columns,
+                rows: page.rows,
+                done,
+                error: None,
+            },
+        );
+
+        if done {
+            QUERY_STREAMS.write().remove(&token);
+            return;
+        }
+
+        offset += chunk_size;
+    }
+}
@@ -0,0 +1,175 @@
+// 连接密码等敏感信息的存储后端：优先使用系统密钥链（macOS Keychain / Windows 凭据管理器 /
+// Linux libsecret），在无 secret service 的无头 Linux 环境下回退到本地加密文件。
+use thiserror::Error;
+
+const SERVICE: &str = "easysql";
+
+#[derive(Error, Debug)]
+pub enum SecretError {
+    #[error("密钥链访问失败: {0}")]
+    KeyringError(String),
+    #[error("本地加密存储失败: {0}")]
+    FallbackError(String),
+}
+
+// 一个连接上可能需要托管的敏感字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretField {
+    Password,
+    SshPassword,
+    SshKey,
+}
+
+impl SecretField {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SecretField::Password => "password",
+            SecretField::SshPassword => "ssh_password",
+            SecretField::SshKey => "ssh_key",
+        }
+    }
+}
+
+fn entry_key(connection_id: &str, field: SecretField) -> String {
+    format!("{}:{}", connection_id, field.as_str())
+}
+
+pub fn store(connection_id: &str, field: SecretField, value: &str) -> Result<(), SecretError> {
+    let key = entry_key(connection_id, field);
+    match keyring::Entry::new(SERVICE, &key) {
+        Ok(entry) => match entry.set_password(value) {
+            Ok(_) => Ok(()),
+            Err(_) => fallback::store(&key, value),
+        },
+        Err(_) => fallback::store(&key, value),
+    }
+}
+
+pub fn load(connection_id: &str, field: SecretField) -> Option<String> {
+    let key = entry_key(connection_id, field);
+    if let Ok(entry) = keyring::Entry::new(SERVICE, &key) {
+        if let Ok(value) = entry.get_password() {
+            return Some(value);
+        }
+    }
+    fallback::load(&key)
+}
+
+pub fn delete(connection_id: &str, field: SecretField) {
+    let key = entry_key(connection_id, field);
+    if let Ok(entry) = keyring::Entry::new(SERVICE, &key) {
+        let _ = entry.delete_credential();
+    }
+    fallback::delete(&key);
+}
+
+// 无 secret service 可用时的本地加密文件回退方案：AES-256-GCM，密钥随机生成后保存在
+// 用户配置目录下，文件权限仅限当前用户可读。
+mod fallback {
+    use super::SecretError;
+    use aes_gcm::aead::{Aead, KeyInit, OsRng};
+    use aes_gcm::{Aes256Gcm, Nonce};
+    use base64::Engine;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    use once_cell::sync::Lazy;
+
+    static STORE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+    fn store_dir() -> PathBuf {
+        let dir = dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("easysql");
+        fs::create_dir_all(&dir).ok();
+        dir
+    }
+
+    fn key_path() -> PathBuf {
+        store_dir().join("secret.key")
+    }
+
+    fn store_path() -> PathBuf {
+        store_dir().join("secrets.enc")
+    }
+
+    fn load_or_create_key() -> Result<Aes256Gcm, SecretError> {
+        let path = key_path();
+        let key_bytes = if path.exists() {
+            let raw = fs::read(&path).map_err(|e| SecretError::FallbackError(e.to_string()))?;
+            base64::engine::general_purpose::STANDARD
+                .decode(raw)
+                .map_err(|e| SecretError::FallbackError(e.to_string()))?
+        } else {
+            let key = Aes256Gcm::generate_key(OsRng).to_vec();
+            let encoded = base64::engine::general_purpose::STANDARD.encode(&key);
+            fs::write(&path, encoded).map_err(|e| SecretError::FallbackError(e.to_string()))?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                if let Ok(meta) = fs::metadata(&path) {
+                    let mut perms = meta.permissions();
+                    perms.set_mode(0o600);
+                    let _ = fs::set_permissions(&path, perms);
+                }
+            }
+            key
+        };
+        Ok(Aes256Gcm::new_from_slice(&key_bytes).map_err(|e| SecretError::FallbackError(e.to_string()))?)
+    }
+
+    fn read_store() -> HashMap<String, String> {
+        fs::read_to_string(store_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn write_store(map: &HashMap<String, String>) -> Result<(), SecretError> {
+        let json = serde_json::to_string_pretty(map).map_err(|e| SecretError::FallbackError(e.to_string()))?;
+        fs::write(store_path(), json).map_err(|e| SecretError::FallbackError(e.to_string()))
+    }
+
+    pub fn store(key: &str, value: &str) -> Result<(), SecretError> {
+        let _guard = STORE_LOCK.lock().unwrap();
+        let cipher = load_or_create_key()?;
+        let nonce_bytes: [u8; 12] = rand::random();
+        let nonce = Nonce::from_slice(&nonce_bytes);
+        let ciphertext = cipher
+            .encrypt(nonce, value.as_bytes())
+            .map_err(|e| SecretError::FallbackError(e.to_string()))?;
+
+        let mut blob = nonce_bytes.to_vec();
+        blob.extend_from_slice(&ciphertext);
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&blob);
+
+        let mut map = read_store();
+        map.insert(key.to_string(), encoded);
+        write_store(&map)
+    }
+
+    pub fn load(key: &str) -> Option<String> {
+        let _guard = STORE_LOCK.lock().unwrap();
+        let cipher = load_or_create_key().ok()?;
+        let map = read_store();
+        let encoded = map.get(key)?;
+        let blob = base64::engine::general_purpose::STANDARD.decode(encoded).ok()?;
+        if blob.len() < 12 {
+            return None;
+        }
+        let (nonce_bytes, ciphertext) = blob.split_at(12);
+        let nonce = Nonce::from_slice(nonce_bytes);
+        let plaintext = cipher.decrypt(nonce, ciphertext).ok()?;
+        String::from_utf8(plaintext).ok()
+    }
+
+    pub fn delete(key: &str) {
+        let _guard = STORE_LOCK.lock().unwrap();
+        let mut map = read_store();
+        if map.remove(key).is_some() {
+            let _ = write_store(&map);
+        }
+    }
+}
@@ -2,6 +2,7 @@ use std::net::{TcpListener, TcpStream};
 use std::io::{Read, Write};
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 use ssh2::Session;
 use thiserror::Error;
 
@@ -15,6 +16,16 @@ pub enum SshError {
     TunnelError(String),
 }
 
+// 建立隧道所需的认证材料，按优先级选用其中一种：私钥（内容优先于文件路径）> ssh-agent > 密码；
+// 只用排在最前的那一种，它失败时不会再尝试其余方式
+pub struct SshAuth {
+    pub password: Option<String>,
+    pub key_path: Option<String>,
+    pub key_data: Option<String>,
+    pub key_passphrase: Option<String>,
+    pub use_agent: bool,
+}
+
 pub struct SshTunnel {
     pub local_port: u16,
     _handle: Option<thread::JoinHandle<()>>,
@@ -25,8 +36,7 @@ impl SshTunnel {
         ssh_host: &str,
         ssh_port: u16,
         ssh_user: &str,
-        ssh_password: Option<&str>,
-        ssh_key: Option<&str>,
+        auth: SshAuth,
         remote_host: &str,
         remote_port: u16,
     ) -> Result<Self, SshError> {
@@ -39,8 +49,6 @@ impl SshTunnel {
 
         let ssh_host = ssh_host.to_string();
         let ssh_user = ssh_user.to_string();
-        let ssh_password = ssh_password.map(|s| s.to_string());
-        let ssh_key = ssh_key.map(|s| s.to_string());
         let remote_host = remote_host.to_string();
 
         // 在后台线程中运行隧道
@@ -50,8 +58,7 @@ impl SshTunnel {
                 &ssh_host,
                 ssh_port,
                 &ssh_user,
-                ssh_password.as_deref(),
-                ssh_key.as_deref(),
+                auth,
                 &remote_host,
                 remote_port,
             );
@@ -67,13 +74,45 @@ impl SshTunnel {
     }
 }
 
+fn authenticate(sess: &Session, user: &str, auth: &SshAuth) -> Result<(), SshError> {
+    if let Some(key_data) = &auth.key_data {
+        return sess
+            .userauth_pubkey_memory(user, None, key_data, auth.key_passphrase.as_deref())
+            .map_err(|e| SshError::AuthError(e.to_string()));
+    }
+
+    if let Some(key_path) = &auth.key_path {
+        return sess
+            .userauth_pubkey_file(
+                user,
+                None,
+                std::path::Path::new(key_path),
+                auth.key_passphrase.as_deref(),
+            )
+            .map_err(|e| SshError::AuthError(e.to_string()));
+    }
+
+    if auth.use_agent {
+        return sess
+            .userauth_agent(user)
+            .map_err(|e| SshError::AuthError(e.to_string()));
+    }
+
+    if let Some(password) = &auth.password {
+        return sess
+            .userauth_password(user, password)
+            .map_err(|e| SshError::AuthError(e.to_string()));
+    }
+
+    Err(SshError::AuthError("需要密码、私钥或 ssh-agent 中的一种认证方式".to_string()))
+}
+
 fn run_tunnel(
     listener: TcpListener,
     ssh_host: &str,
     ssh_port: u16,
     ssh_user: &str,
-    ssh_password: Option<&str>,
-    ssh_key: Option<&str>,
+    auth: SshAuth,
     remote_host: &str,
     remote_port: u16,
 ) {
@@ -100,58 +139,46 @@ fn run_tunnel(
         return;
     }
 
-    // 认证
-    let auth_result = if let Some(key_path) = ssh_key {
-        sess.userauth_pubkey_file(ssh_user, None, std::path::Path::new(key_path), None)
-    } else if let Some(password) = ssh_password {
-        sess.userauth_password(ssh_user, password)
-    } else {
-        tracing::error!("SSH 需要密码或密钥");
-        return;
-    };
-
-    if let Err(e) = auth_result {
+    if let Err(e) = authenticate(&sess, ssh_user, &auth) {
         tracing::error!("SSH 认证失败: {}", e);
         return;
     }
 
-    let sess = Arc::new(sess);
+    // 每 30 秒发一次 keepalive，避免空闲隧道被服务端断开
+    sess.set_keepalive(true, 30);
+
+    // libssh2 的 Session 不是线程安全的，同一时刻只能有一个线程在调用它的方法——这条
+    // 限制不仅覆盖 channel_direct_tcpip/set_blocking/keepalive_send 这类直接操作
+    // Session 的调用，也覆盖所有 Channel 的读写：Channel 在 libssh2 里共享其所属
+    // Session 的底层传输（同一个 socket/协议状态机），并不是各自独立的连接，对某个
+    // Channel 的读写和对 Session 本身的调用并发执行一样会打乱协议状态机。因此整条
+    // SSH 连接只用这一把 Mutex<Session>：keepalive 线程、每条隧道的 channel_direct_tcpip，
+    // 以及 relay 里针对 channel 的所有读写，全部经这同一把锁串行化，而不是给 channel
+    // 再配一把独立的锁（独立锁只能保证同一 channel 内部读写互斥，挡不住它和 keepalive
+    // 之类直接操作 Session 的调用之间的竞争）。
+    let sess = Arc::new(std::sync::Mutex::new(sess));
+
+    {
+        let sess = sess.clone();
+        thread::spawn(move || loop {
+            thread::sleep(Duration::from_secs(30));
+            if sess.lock().unwrap().keepalive_send().is_err() {
+                break;
+            }
+        });
+    }
 
     // 监听本地连接并转发
     for stream in listener.incoming() {
         match stream {
-            Ok(mut local_stream) => {
+            Ok(local_stream) => {
                 let sess = sess.clone();
                 let remote_host = remote_host.to_string();
-                
+
                 thread::spawn(move || {
-                    match sess.channel_direct_tcpip(&remote_host, remote_port, None) {
-                        Ok(mut channel) => {
-                            let mut buf = [0u8; 8192];
-                            loop {
-                                // 从本地读取
-                                match local_stream.read(&mut buf) {
-                                    Ok(0) => break,
-                                    Ok(n) => {
-                                        if channel.write_all(&buf[..n]).is_err() {
-                                            break;
-                                        }
-                                    }
-                                    Err(_) => break,
-                                }
-
-                                // 从远程读取
-                                match channel.read(&mut buf) {
-                                    Ok(0) => break,
-                                    Ok(n) => {
-                                        if local_stream.write_all(&buf[..n]).is_err() {
-                                            break;
-                                        }
-                                    }
-                                    Err(_) => break,
-                                }
-                            }
-                        }
+                    let channel = sess.lock().unwrap().channel_direct_tcpip(&remote_host, remote_port, None);
+                    match channel {
+                        Ok(channel) => relay(local_stream, channel, sess),
                         Err(e) => {
                             tracing::error!("创建 SSH 通道失败: {}", e);
                         }
@@ -165,3 +192,123 @@ fn run_tunnel(
     }
 }
 
+// upload 向 channel 写，download 从 channel 读，写和读会争用同一把锁；之前 download
+// 在持锁状态下调用的是阻塞 read()，只要远程一时没有数据，upload 就永远抢不到锁。
+// 这里把 session 切成非阻塞模式，read/write 遇到 EAGAIN 会立刻以 WouldBlock 返回而
+// 不是挂起等待，于是持锁的时间只够做一次非阻塞的读/写尝试，两个方向不会再互相卡死。
+//
+// channel 本身仍然包一层 Mutex<Channel>，但那只是为了让同一个 Channel 能被 upload/
+// download 两个线程共享所有权——真正提供互斥的是 sess 这把 Mutex<Session>：每次读写
+// channel 之前都先 sess.lock()，和 run_tunnel 里 keepalive_send/channel_direct_tcpip
+// 锁的是同一把锁，确保任意时刻只有一个线程在触碰这条 SSH 连接（包括它名下的 channel），
+// 不会出现 keepalive 线程和数据转发线程各自独立加锁、并发踩 libssh2 协议状态机的情况
+fn relay(local_stream: TcpStream, channel: ssh2::Channel, sess: Arc<std::sync::Mutex<Session>>) {
+    sess.lock().unwrap().set_blocking(false);
+    if let Err(e) = local_stream.set_nonblocking(true) {
+        tracing::error!("设置本地连接为非阻塞模式失败: {}", e);
+        return;
+    }
+
+    let channel = Arc::new(std::sync::Mutex::new(channel));
+
+    let mut local_read = match local_stream.try_clone() {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::error!("克隆本地连接失败: {}", e);
+            return;
+        }
+    };
+    let mut local_write = local_stream;
+
+    let upload = {
+        let channel = channel.clone();
+        let sess = sess.clone();
+        thread::spawn(move || {
+            let mut buf = [0u8; 8192];
+            loop {
+                match local_read.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        if !channel_write_all(&sess, &channel, &buf[..n]) {
+                            break;
+                        }
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(10));
+                    }
+                    Err(_) => break,
+                }
+            }
+            let _sess_guard = sess.lock().unwrap();
+            let mut channel = channel.lock().unwrap();
+            let _ = channel.send_eof();
+        })
+    };
+
+    let download = {
+        let sess = sess.clone();
+        thread::spawn(move || {
+            let mut buf = [0u8; 8192];
+            loop {
+                let read_result = {
+                    let _sess_guard = sess.lock().unwrap();
+                    let mut channel = channel.lock().unwrap();
+                    channel.read(&mut buf)
+                };
+                match read_result {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        if !tcp_write_all(&mut local_write, &buf[..n]) {
+                            break;
+                        }
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(10));
+                    }
+                    Err(_) => break,
+                }
+            }
+            let _ = local_write.shutdown(std::net::Shutdown::Both);
+        })
+    };
+
+    let _ = upload.join();
+    let _ = download.join();
+}
+
+// channel.write() 在非阻塞模式下可能只写入部分字节或返回 WouldBlock；每次尝试读写前都
+// 先拿 sess 的锁再拿 channel 的锁（顺序固定，不会死锁），锁只在真正尝试读写的一瞬间
+// 持有，等待重试时两把锁一起释放，让另一个方向、以及 keepalive 线程有机会拿到 sess 锁
+fn channel_write_all(sess: &Arc<std::sync::Mutex<Session>>, channel: &Arc<std::sync::Mutex<ssh2::Channel>>, mut buf: &[u8]) -> bool {
+    while !buf.is_empty() {
+        let result = {
+            let _sess_guard = sess.lock().unwrap();
+            let mut channel = channel.lock().unwrap();
+            channel.write(buf)
+        };
+        match result {
+            Ok(n) if n > 0 => buf = &buf[n..],
+            Ok(_) => return false,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(_) => return false,
+        }
+    }
+    true
+}
+
+// 本地 TcpStream 也被设成了非阻塞，write_all 同样可能遇到 WouldBlock，需要自己重试
+fn tcp_write_all(stream: &mut TcpStream, mut buf: &[u8]) -> bool {
+    while !buf.is_empty() {
+        match stream.write(buf) {
+            Ok(n) if n > 0 => buf = &buf[n..],
+            Ok(_) => return false,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(_) => return false,
+        }
+    }
+    true
+}
@@ -0,0 +1,355 @@
+// 表订阅：前端对某条查询（而不是整张表）建立实时订阅，代替轮询 db_get_table_data。
+// db_update_row/db_delete_row 以及任何改动型 db_query 执行后，会按它们涉及的表名
+// 找到相关订阅，重新执行一遍订阅的 SQL，按主键把新旧快照做一次 diff，只把增量
+// （新增/更新/删除的行）通过 "query-subscription-change" 事件推给前端。
+// 事件名特意与 cdc.rs 里基于 Postgres 逻辑复制的 "query-change" 区分开，两者语义不同。
+use crate::commands;
+use crate::database::{self, DbConnection, CONNECTIONS};
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::RwLock;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Emitter};
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionChange {
+    pub token: String,
+    pub columns: Vec<String>,
+    pub added: Vec<Vec<Value>>,
+    pub updated: Vec<Vec<Value>>,
+    pub removed: Vec<Vec<Value>>,
+}
+
+struct Subscription {
+    connection_id: String,
+    database: String,
+    sql: String,
+    tables: HashSet<String>,
+    pk_index: Option<usize>,
+    columns: Vec<String>,
+    snapshot: HashMap<String, Vec<Value>>,
+    subscriber_count: u32,
+}
+
+// key 为 "连接id::database::归一化后的 SQL"，这样多个标签页订阅同一条查询时共用一份快照；
+// database 必须参与 key，否则同一条连接上针对不同库的同名查询会被错误地合并成一份快照
+static SUBSCRIPTIONS: Lazy<RwLock<HashMap<String, Subscription>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+
+pub fn init(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+// 连接断开时清掉它名下的所有订阅
+pub fn drop_connection(id: &str) {
+    SUBSCRIPTIONS.write().retain(|_, sub| sub.connection_id != id);
+}
+
+fn normalize_sql(sql: &str) -> String {
+    sql.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+// 极简 tokenizer：按空白、括号/逗号/分号、引号片段切分，够用来在 FROM/JOIN/INTO/UPDATE
+// 后面找表名，不追求覆盖完整 SQL 语法（真正的解析留给以后接入 sqlite3_parser 之类的库）
+fn tokenize(sql: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+    let mut chars = sql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+        } else if matches!(c, ',' | '(' | ')' | ';') {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            tokens.push(c.to_string());
+        } else if matches!(c, '\'' | '"' | '`') {
+            current.push(c);
+            for next in chars.by_ref() {
+                current.push(next);
+                if next == c {
+                    break;
+                }
+            }
+            tokens.push(std::mem::take(&mut current));
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+pub fn extract_referenced_tables(sql: &str) -> HashSet<String> {
+    let tokens = tokenize(sql);
+    let mut tables = HashSet::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        let lower = token.to_lowercase();
+        if matches!(lower.as_str(), "from" | "join" | "into" | "update") {
+            if let Some(next) = tokens.get(i + 1) {
+                if next == "(" {
+                    continue;
+                }
+                let name = next.trim_matches(|c| c == '`' || c == '"' || c == '[' || c == ']');
+                let name = name.rsplit('.').next().unwrap_or(name);
+                if !name.is_empty() {
+                    tables.insert(name.to_lowercase());
+                }
+            }
+        }
+    }
+
+    tables
+}
+
+#[cfg(test)]
+mod tokenize_tests {
+    use super::*;
+
+    #[test]
+    fn splits_on_whitespace_and_punctuation() {
+        let tokens = tokenize("SELECT a, b FROM t WHERE a = 1;");
+        assert_eq!(
+            tokens,
+            vec!["SELECT", "a", ",", "b", "FROM", "t", "WHERE", "a", "=", "1", ";"]
+        );
+    }
+
+    #[test]
+    fn keeps_quoted_identifiers_and_strings_as_single_tokens() {
+        let tokens = tokenize("SELECT * FROM `my table` WHERE name = 'a b'");
+        assert!(tokens.contains(&"`my table`".to_string()));
+        assert!(tokens.contains(&"'a b'".to_string()));
+    }
+
+    #[test]
+    fn extract_referenced_tables_finds_from_join_into_update() {
+        let tables = extract_referenced_tables("SELECT * FROM users u JOIN orders o ON u.id = o.user_id");
+        assert!(tables.contains("users"));
+        assert!(tables.contains("orders"));
+    }
+
+    #[test]
+    fn extract_referenced_tables_strips_schema_prefix() {
+        let tables = extract_referenced_tables("SELECT * FROM db.my_table");
+        assert!(tables.contains("my_table"));
+    }
+
+    #[test]
+    fn extract_referenced_tables_ignores_derived_table_subquery() {
+        let tables = extract_referenced_tables("SELECT * FROM (SELECT 1) t");
+        assert!(!tables.contains("("));
+    }
+
+    #[test]
+    fn extract_referenced_tables_handles_update_and_into() {
+        let tables = extract_referenced_tables("UPDATE accounts SET balance = 1");
+        assert!(tables.contains("accounts"));
+        let tables = extract_referenced_tables("INSERT INTO logs VALUES (1)");
+        assert!(tables.contains("logs"));
+    }
+}
+
+fn build_snapshot(rows: &[Vec<Value>], pk_index: Option<usize>) -> HashMap<String, Vec<Value>> {
+    rows.iter()
+        .map(|row| {
+            let key = match pk_index.and_then(|i| row.get(i)) {
+                Some(v) => v.to_string(),
+                None => serde_json::to_string(row).unwrap_or_default(),
+            };
+            (key, row.clone())
+        })
+        .collect()
+}
+
+// MySQL/SQL Server 同一条连接能在运行时切库，订阅的 SQL 文本本身不带库名，所以重新执行
+// 前要先切到目标库；Postgres/SQLite 的"库"在连接建立时就已固定，没有运行时切库这回事，
+// 原样执行即可。
+//
+// SQL Server 走 `query_sqlserver` 的 `simple_query`，支持一次发多条语句，所以可以把
+// `USE [db]; sql` 拼成一条串，在单次 pool.get() 拿到的同一条连接上一次发完，没有连接
+// 错位的风险。MySQL 不行：`sqlx::query(sql).fetch_all(pool)` 这条路径一律走二进制预处理
+// 协议，一次只能 prepare 一条语句，拼接的 `USE db; sql` 会在 prepare 阶段报语法错误，
+// 只能拆成两条语句——但拆开不能靠各自独立调用 `db_query`/`use_database`，那是两次独立
+// 的 `pool.acquire()`，池里只要有一条以上的空闲连接就不保证落在同一条物理连接上，USE
+// 和订阅 SQL 就可能在不同连接上执行，订阅刷新会悄悄对错库跑查询、把错误的增量推给前端
+// （和 chunk2-1 里 db_update_row/db_delete_row 本来的坑一样）。所以 MySQL 这条分支改走
+// `commands::query_mysql_scoped`，在同一条取出的连接上顺序跑 USE 和订阅 SQL。
+async fn query_scoped(id: &str, database: &str, sql: &str) -> database::QueryResult {
+    let conn_info = {
+        let connections = CONNECTIONS.read();
+        match connections.get(id) {
+            Some(c) => c.clone(),
+            None => return database::QueryResult {
+                columns: vec![],
+                rows: vec![],
+                error: Some("未连接".to_string()),
+                affected_rows: None,
+                error_detail: None,
+            },
+        }
+    };
+
+    match &conn_info.connection {
+        DbConnection::MySql(pool) => commands::query_mysql_scoped(pool, database, sql).await,
+        DbConnection::SqlServer(_) => commands::db_query(id.to_string(), format!("USE [{}]; {}", database, sql)).await,
+        DbConnection::Postgres(_) | DbConnection::Sqlite(_) => commands::db_query(id.to_string(), sql.to_string()).await,
+    }
+}
+
+// 只有订阅恰好涉及一张表时才能把结果列对应回该表的主键列；多表 join 的订阅退化为
+// 按整行内容 diff（见 build_snapshot 在 pk_index 为 None 时的分支）
+async fn find_primary_key_index(id: &str, database: &str, tables: &HashSet<String>, columns: &[String]) -> Option<usize> {
+    if tables.len() != 1 {
+        return None;
+    }
+    let table = tables.iter().next()?.clone();
+    let col_infos = commands::db_get_columns(id.to_string(), database.to_string(), table).await;
+    let pk_name = col_infos.iter().find(|c| c.key.as_deref() == Some("PRI"))?.name.clone();
+    columns.iter().position(|c| c == &pk_name)
+}
+
+#[tauri::command]
+pub async fn db_subscribe(id: String, database: String, sql: String) -> Result<String, String> {
+    if CONNECTIONS.read().get(&id).is_none() {
+        return Err("未连接".to_string());
+    }
+
+    let token = format!("{}::{}::{}", id, database, normalize_sql(&sql));
+
+    {
+        let mut subs = SUBSCRIPTIONS.write();
+        if let Some(existing) = subs.get_mut(&token) {
+            existing.subscriber_count += 1;
+            return Ok(token);
+        }
+    }
+
+    let tables = extract_referenced_tables(&sql);
+    let result = query_scoped(&id, &database, &sql).await;
+    if let Some(err) = result.error {
+        return Err(err);
+    }
+
+    let pk_index = find_primary_key_index(&id, &database, &tables, &result.columns).await;
+    let snapshot = build_snapshot(&result.rows, pk_index);
+
+    SUBSCRIPTIONS.write().insert(
+        token.clone(),
+        Subscription {
+            connection_id: id,
+            database,
+            sql,
+            tables,
+            pk_index,
+            columns: result.columns,
+            snapshot,
+            subscriber_count: 1,
+        },
+    );
+
+    Ok(token)
+}
+
+#[tauri::command]
+pub fn db_unsubscribe(token: String) -> bool {
+    let mut subs = SUBSCRIPTIONS.write();
+    match subs.get_mut(&token) {
+        Some(sub) if sub.subscriber_count > 1 => {
+            sub.subscriber_count -= 1;
+            true
+        }
+        Some(_) => {
+            subs.remove(&token);
+            true
+        }
+        None => false,
+    }
+}
+
+// db_update_row/db_delete_row/db_query 在写操作成功后调用：找出这条连接下涉及到
+// changed_tables 里任意一张表的订阅，逐个重新执行并把 diff 推给前端
+pub async fn notify_tables_changed(id: &str, changed_tables: &HashSet<String>) {
+    if changed_tables.is_empty() {
+        return;
+    }
+
+    let tokens: Vec<String> = {
+        let subs = SUBSCRIPTIONS.read();
+        subs.iter()
+            .filter(|(_, sub)| sub.connection_id == id && !sub.tables.is_disjoint(changed_tables))
+            .map(|(token, _)| token.clone())
+            .collect()
+    };
+
+    for token in tokens {
+        refresh_subscription(&token).await;
+    }
+}
+
+async fn refresh_subscription(token: &str) {
+    let app = match APP_HANDLE.get() {
+        Some(app) => app.clone(),
+        None => return,
+    };
+
+    let (id, database, sql, pk_index, old_snapshot) = {
+        let subs = SUBSCRIPTIONS.read();
+        let sub = match subs.get(token) {
+            Some(s) => s,
+            None => return,
+        };
+        (sub.connection_id.clone(), sub.database.clone(), sub.sql.clone(), sub.pk_index, sub.snapshot.clone())
+    };
+
+    let result = query_scoped(&id, &database, &sql).await;
+    if result.error.is_some() {
+        return;
+    }
+
+    let new_snapshot = build_snapshot(&result.rows, pk_index);
+
+    let mut added = vec![];
+    let mut updated = vec![];
+    for (key, row) in &new_snapshot {
+        match old_snapshot.get(key) {
+            None => added.push(row.clone()),
+            Some(old_row) if old_row != row => updated.push(row.clone()),
+            _ => {}
+        }
+    }
+    let removed: Vec<Vec<Value>> = old_snapshot
+        .iter()
+        .filter(|(key, _)| !new_snapshot.contains_key(*key))
+        .map(|(_, row)| row.clone())
+        .collect();
+
+    if added.is_empty() && updated.is_empty() && removed.is_empty() {
+        return;
+    }
+
+    let columns = result.columns.clone();
+    if let Some(sub) = SUBSCRIPTIONS.write().get_mut(token) {
+        sub.snapshot = new_snapshot;
+        sub.columns = columns.clone();
+    }
+
+    let _ = app.emit(
+        "query-subscription-change",
+        SubscriptionChange {
+            token: token.to_string(),
+            columns,
+            added,
+            updated,
+            removed,
+        },
+    );
+}